@@ -1,4 +1,4 @@
-use ruiso::{Featurizable, StructFeature};
+use ruiso::{Featurizable, Featurizer, StructFeature};
 
 #[derive(StructFeature)]
 pub struct SimpleTestStruct {
@@ -0,0 +1,70 @@
+//! Reports hash collisions for a sample of string values, one per line.
+//!
+//! ```text
+//! ruiso-collisions [--dim N] [--target RATE] [FILE]
+//! ```
+//! Reads from stdin when no file is given.
+
+use ruiso::collision::analyze;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader};
+use std::process;
+
+const USAGE: &str = "usage: ruiso-collisions [--dim N] [--target RATE] [FILE]";
+
+fn parse_or_exit<T: std::str::FromStr>(flag: &str, value: Option<String>) -> T {
+    match value.as_ref().map(|v| v.parse()) {
+        Some(Ok(v)) => v,
+        _ => {
+            eprintln!("{} needs a numeric value\n{}", flag, USAGE);
+            process::exit(2);
+        }
+    }
+}
+
+fn main() {
+    let mut dim: usize = 37;
+    let mut target: f64 = 0.01;
+    let mut path: Option<String> = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--dim" => dim = parse_or_exit("--dim", args.next()),
+            "--target" => target = parse_or_exit("--target", args.next()),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return;
+            }
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(arg),
+            _ => {
+                eprintln!("unexpected argument {}\n{}", arg, USAGE);
+                process::exit(2);
+            }
+        }
+    }
+    if dim == 0 {
+        eprintln!("--dim must be at least 1");
+        process::exit(2);
+    }
+
+    let reader: Box<dyn BufRead> = match &path {
+        Some(p) => match File::open(p) {
+            Ok(f) => Box::new(BufReader::new(f)),
+            Err(e) => {
+                eprintln!("could not open {}: {}", p, e);
+                process::exit(1);
+            }
+        },
+        None => Box::new(BufReader::new(io::stdin())),
+    };
+    let values: Vec<String> = match reader.lines().collect() {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("could not read values: {}", e);
+            process::exit(1);
+        }
+    };
+
+    println!("{}", analyze(&values, dim, target));
+}
@@ -0,0 +1,94 @@
+//! # Numeric buckets
+//!
+//! One hot bins for numeric fields. `#[struct_feature(buckets = [0, 10, 100])]` uses fixed
+//! boundaries, `#[struct_feature(quantiles = 4)]` fits them so each bin holds about the same
+//! share of the training data.
+
+use crate::fitted::{invalid, parse, read_line, Persist};
+use std::io::{self, BufRead, Write};
+
+/// The bin `x` falls in for sorted `boundaries`: bin 0 is below the first boundary,
+/// bin `j` is `boundaries[j - 1] <= x < boundaries[j]` and the last bin is at or above the last boundary.
+/// NaN falls in no bin.
+#[inline]
+pub fn bucket_index(x: f64, boundaries: &[f64]) -> Option<usize> {
+    if x.is_nan() {
+        None
+    } else {
+        Some(boundaries.partition_point(|b| *b <= x))
+    }
+}
+
+/// # Buckets
+/// Fitted bucket boundaries, giving `boundaries.len() + 1` bins.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Buckets {
+    /// Sorted boundaries between the bins
+    pub boundaries: Vec<f64>,
+}
+
+impl Buckets {
+    /// Number of bins
+    pub fn dim(&self) -> usize {
+        self.boundaries.len() + 1
+    }
+
+    /// The bin `x` falls in, see `bucket_index`
+    #[inline]
+    pub fn index(&self, x: f64) -> Option<usize> {
+        bucket_index(x, &self.boundaries)
+    }
+}
+
+impl Persist for Buckets {
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write!(w, "{}", self.boundaries.len())?;
+        for b in self.boundaries.iter() {
+            write!(w, " {}", b)?;
+        }
+        writeln!(w)
+    }
+
+    fn read_from<R: BufRead>(r: &mut R) -> io::Result<Self> {
+        let line = read_line(r)?;
+        let mut parts = line.split(' ');
+        let count: usize = parse(parts.next(), "boundary count")?;
+        let boundaries = parts
+            .map(|b| parse(Some(b), "boundary"))
+            .collect::<io::Result<Vec<f64>>>()?;
+        if boundaries.len() != count {
+            return Err(invalid("wrong number of bucket boundaries"));
+        }
+        Ok(Buckets { boundaries })
+    }
+}
+
+/// # Quantile Sketch
+/// Collects the values of a field to fit quantile boundaries. Keeps every value, so memory grows with the data.
+#[derive(Debug, Clone, Default)]
+pub struct QuantileSketch {
+    values: Vec<f64>,
+}
+
+impl QuantileSketch {
+    /// Adds a value. NaN and infinities are skipped.
+    #[inline]
+    pub fn observe(&mut self, x: f64) {
+        if x.is_finite() {
+            self.values.push(x);
+        }
+    }
+
+    /// Boundaries splitting the values into `bins` bins of about equal size.
+    /// Heavily repeated values give repeated boundaries, and so empty bins.
+    pub fn finish(&self, bins: usize) -> Buckets {
+        assert!(bins > 0, "Need at least one quantile bin");
+        let mut sorted = self.values.clone();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        let n = sorted.len();
+        let boundaries = (1..bins)
+            .map(|j| if n == 0 { 0.0 } else { sorted[(j * n / bins).min(n - 1)] })
+            .collect();
+        Buckets { boundaries }
+    }
+}
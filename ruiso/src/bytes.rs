@@ -0,0 +1,250 @@
+//! # Bytes
+//!
+//! Features of binary data, like file contents and packet payloads: the share of every byte value,
+//! the byte-entropy histogram of Saxe and Berlin and the total length.
+//! `#[struct_feature(bytes(histogram, entropy, length))]` writes them for `Vec<u8>` and `&[u8]` fields,
+//! and `ByteHistogram` and `ByteEntropyHistogram` are standalone featurizers.
+//!
+//! `ByteNgramHasher` hashes runs of `n` bytes, from a slice or streamed from any `Read` so whole files
+//! don't have to be loaded. `#[struct_feature(byte_ngrams(n = 4, stride = 1, max_bytes = 65536, dim = 1024))]`
+//! hashes a field and `make_byte_ngram_feature!` builds a standalone featurizer.
+
+use crate::collision::hash_bucket;
+use std::io::{self, Read};
+
+/// Sliding window of the entropy histogram, in bytes
+pub const ENTROPY_WINDOW: usize = 2048;
+/// Step between entropy windows, in bytes
+pub const ENTROPY_STEP: usize = 1024;
+
+/// Writes the share of every byte value into 256 columns
+pub fn fill_byte_histogram(data: &[u8], slice: &mut [f32]) {
+    if data.is_empty() {
+        return;
+    }
+    let mut counts = [0u64; 256];
+    for b in data {
+        counts[*b as usize] += 1;
+    }
+    let total = data.len() as f32;
+    for (s, c) in slice.iter_mut().zip(counts.iter()) {
+        *s = *c as f32 / total;
+    }
+}
+
+/// Writes the joint histogram of (window entropy, byte value) into 256 columns, 16 entropy rows of 16 nibble columns.
+/// Every window of `window` bytes, `step` bytes apart, has its entropy taken over the high nibbles of its bytes,
+/// from 0 to 4 bits, split into 16 rows, and adds the counts of its nibbles to its row. Data shorter than
+/// a window is one window. The histogram is normalized to sum to 1.
+pub fn fill_byte_entropy(data: &[u8], window: usize, step: usize, slice: &mut [f32]) {
+    assert!(window > 0 && step > 0, "Byte entropy needs a window and a step");
+    if data.is_empty() {
+        return;
+    }
+    let mut hist = [0u64; 256];
+    let mut add_window = |w: &[u8]| {
+        let mut counts = [0u64; 16];
+        for b in w {
+            counts[(*b >> 4) as usize] += 1;
+        }
+        let n = w.len() as f64;
+        let entropy: f64 = counts
+            .iter()
+            .filter(|c| **c > 0)
+            .map(|c| {
+                let p = *c as f64 / n;
+                -p * p.log2()
+            })
+            .sum();
+        let row = ((entropy * 4.0) as usize).min(15);
+        for (nibble, c) in counts.iter().enumerate() {
+            hist[row * 16 + nibble] += c;
+        }
+    };
+    if data.len() <= window {
+        add_window(data);
+    } else {
+        let mut start = 0;
+        while start + window <= data.len() {
+            add_window(&data[start..start + window]);
+            start += step;
+        }
+    }
+    let total: u64 = hist.iter().sum();
+    for (s, c) in slice.iter_mut().zip(hist.iter()) {
+        *s = *c as f32 / total as f32;
+    }
+}
+
+/// # Byte Histogram
+/// Featurizer writing the share of every byte value.
+#[derive(Debug)]
+pub struct ByteHistogram {}
+
+impl<T: AsRef<[u8]>> crate::Featurizer<T> for ByteHistogram {
+    #[inline]
+    fn dim() -> usize {
+        256
+    }
+    #[inline]
+    fn fill_slice(data: &T, slice: &mut [f32]) {
+        fill_byte_histogram(data.as_ref(), slice);
+    }
+    fn default(_slice: &mut [f32]) {}
+}
+
+/// # Byte Entropy Histogram
+/// Featurizer writing the byte-entropy histogram with the default window and step.
+#[derive(Debug)]
+pub struct ByteEntropyHistogram {}
+
+impl<T: AsRef<[u8]>> crate::Featurizer<T> for ByteEntropyHistogram {
+    #[inline]
+    fn dim() -> usize {
+        256
+    }
+    #[inline]
+    fn fill_slice(data: &T, slice: &mut [f32]) {
+        fill_byte_entropy(data.as_ref(), ENTROPY_WINDOW, ENTROPY_STEP, slice);
+    }
+    fn default(_slice: &mut [f32]) {}
+}
+
+/// Size of the chunks `ByteNgramHasher::fill_reader` reads
+pub const READ_CHUNK: usize = 8192;
+
+/// # Byte N-gram Hasher
+/// Hashes every run of `n` bytes starting at a multiple of `stride`, within the first `max_bytes` bytes.
+#[derive(Debug, Clone)]
+pub struct ByteNgramHasher {
+    /// Bytes per n-gram
+    pub n: usize,
+    /// Distance between the starts of two n-grams
+    pub stride: usize,
+    /// Most bytes scanned, from the start of the data
+    pub max_bytes: Option<usize>,
+    /// Set columns to 1 instead of counting
+    pub binary: bool,
+}
+
+impl ByteNgramHasher {
+    /// Every n-gram of `n` bytes
+    pub fn new(n: usize) -> Self {
+        assert!(n > 0, "Byte n-grams need n > 0");
+        ByteNgramHasher {
+            n,
+            stride: 1,
+            max_bytes: None,
+            binary: false,
+        }
+    }
+
+    /// Starts n-grams every `stride` bytes
+    pub fn stride(mut self, stride: usize) -> Self {
+        assert!(stride > 0, "Byte n-grams need a stride > 0");
+        self.stride = stride;
+        self
+    }
+
+    /// Scans at most `max_bytes` bytes
+    pub fn max_bytes(mut self, max_bytes: usize) -> Self {
+        self.max_bytes = Some(max_bytes);
+        self
+    }
+
+    /// Marks the n-grams present instead of counting them
+    pub fn binary(mut self) -> Self {
+        self.binary = true;
+        self
+    }
+
+    #[inline]
+    fn add(&self, gram: &[u8], slice: &mut [f32]) {
+        let j = hash_bucket(gram, slice.len());
+        if self.binary {
+            slice[j] = 1.0;
+        } else {
+            slice[j] += 1.0;
+        }
+    }
+
+    /// Hashes the n-grams of `data` into the columns of the slice
+    pub fn fill(&self, data: &[u8], slice: &mut [f32]) {
+        let data = &data[..data.len().min(self.max_bytes.unwrap_or(usize::MAX))];
+        let mut start = 0;
+        while start + self.n <= data.len() {
+            self.add(&data[start..start + self.n], slice);
+            start += self.stride;
+        }
+    }
+
+    /// Same as `fill`, reading the data in chunks and keeping only the bytes of unfinished n-grams
+    pub fn fill_reader<R: Read>(&self, reader: R, slice: &mut [f32]) -> io::Result<()> {
+        let mut reader = reader.take(self.max_bytes.map_or(u64::MAX, |m| m as u64));
+        let mut chunk = vec![0u8; READ_CHUNK];
+        // `pending` holds the bytes from offset `base` on, and `start` is the offset of the next n-gram.
+        let mut pending: Vec<u8> = Vec::new();
+        let mut base = 0;
+        let mut start = 0;
+        loop {
+            let read = match reader.read(&mut chunk) {
+                Ok(0) => return Ok(()),
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            pending.extend_from_slice(&chunk[..read]);
+            while start + self.n <= base + pending.len() {
+                self.add(&pending[start - base..start - base + self.n], slice);
+                start += self.stride;
+            }
+            let done = (start - base).min(pending.len());
+            pending.drain(..done);
+            base += done;
+        }
+    }
+}
+
+/// Builds a featurizer hashing the byte n-grams of binary data with a `ByteNgramHasher`, built once on first use.
+/// The name should end in the number of columns. `featurize_reader` streams the data from a `Read` instead.
+/// ```no_run
+/// # use ruiso::bytes::ByteNgramHasher;
+/// # use ruiso::make_byte_ngram_feature;
+/// # use std::fs::File;
+/// make_byte_ngram_feature!(Payload1024, 1024, ByteNgramHasher::new(4).max_bytes(1 << 20));
+/// # fn main() -> std::io::Result<()> {
+/// let features = Payload1024::featurize_reader(File::open("sample.bin")?)?;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! make_byte_ngram_feature {
+    ($name:ident, $dim:expr, $hasher:expr) => {
+        #[derive(Debug)]
+        pub struct $name {}
+        impl $name {
+            #[doc(hidden)]
+            pub fn ruiso_byte_ngrams() -> &'static $crate::bytes::ByteNgramHasher {
+                static NGRAMS: std::sync::OnceLock<$crate::bytes::ByteNgramHasher> = std::sync::OnceLock::new();
+                NGRAMS.get_or_init(|| $hasher)
+            }
+            /// Featurizes the data read from `reader`, without loading it whole
+            pub fn featurize_reader<R: std::io::Read>(reader: R) -> std::io::Result<Vec<f32>> {
+                let mut features = vec![0.0; $dim];
+                Self::ruiso_byte_ngrams().fill_reader(reader, &mut features)?;
+                Ok(features)
+            }
+        }
+        impl<T: AsRef<[u8]>> $crate::Featurizer<T> for $name {
+            #[inline]
+            fn dim() -> usize {
+                $dim
+            }
+            #[inline]
+            fn fill_slice(data: &T, slice: &mut [f32]) {
+                $name::ruiso_byte_ngrams().fill(data.as_ref(), slice);
+            }
+            fn default(_slice: &mut [f32]) {}
+        }
+    };
+}
@@ -0,0 +1,156 @@
+//! # Hash collision analysis
+//!
+//! Picking the dimension of a hashing trick featurizer is guesswork without looking at the data.
+//! These functions hash a sample of values the same way `make_string_feature!` and the derived
+//! `String` fields do, and report how crowded the buckets get.
+
+use crate::Featurizer;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+/// Number of buckets listed in `CollisionReport::worst_buckets`.
+pub const WORST_BUCKETS: usize = 10;
+
+/// Largest dimension `analyze` will recommend.
+pub const MAX_RECOMMENDED_DIM: usize = 1 << 28;
+
+/// Hashes a value with the hasher used by the hashing trick featurizers.
+#[inline]
+pub fn hash_value<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The bucket a value lands in for a hashing trick of dimension `dim`.
+#[inline]
+pub fn hash_bucket<T: Hash + ?Sized>(value: &T, dim: usize) -> usize {
+    (hash_value(value) as usize) % dim
+}
+
+/// # Collision Report
+/// What happened when a sample of values was hashed into `dim` buckets.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CollisionReport {
+    /// Dimension that was analyzed
+    pub dim: usize,
+    /// Number of values in the sample, duplicates included
+    pub samples: usize,
+    /// Number of distinct values in the sample
+    pub distinct: usize,
+    /// Number of buckets that at least one value landed in
+    pub buckets_used: usize,
+    /// Fraction of distinct values that share their bucket with an earlier value
+    pub collision_rate: f64,
+    /// The most crowded buckets as (bucket, distinct values in it), most crowded first
+    pub worst_buckets: Vec<(usize, usize)>,
+    /// Collision rate the recommendation was made for
+    pub target_rate: f64,
+    /// Smallest dimension found whose collision rate on this sample is at most `target_rate`
+    pub recommended_dim: usize,
+}
+
+fn collision_rate(hashes: &[u64], dim: usize) -> f64 {
+    if hashes.is_empty() {
+        return 0.0;
+    }
+    let used: HashSet<usize> = hashes.iter().map(|h| (*h as usize) % dim).collect();
+    1.0 - used.len() as f64 / hashes.len() as f64
+}
+
+fn recommend_dim(hashes: &[u64], target_rate: f64) -> usize {
+    let mut hi = 1;
+    while collision_rate(hashes, hi) > target_rate {
+        if hi >= MAX_RECOMMENDED_DIM {
+            return MAX_RECOMMENDED_DIM;
+        }
+        hi *= 2;
+    }
+    // The rate is only roughly monotone in the dimension, so this finds a good dimension, not the best one.
+    let mut lo = hi / 2;
+    while lo + 1 < hi {
+        let mid = lo + (hi - lo) / 2;
+        if collision_rate(hashes, mid) > target_rate {
+            lo = mid;
+        } else {
+            hi = mid;
+        }
+    }
+    hi
+}
+
+/// Hashes the values into `dim` buckets and reports the collisions,
+/// along with the dimension needed to get the collision rate down to `target_rate`.
+pub fn analyze<'a, T, I>(values: I, dim: usize, target_rate: f64) -> CollisionReport
+where
+    T: Hash + Eq + ?Sized + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    assert!(dim > 0, "The dimension of a hashing featurizer can't be 0");
+    let mut samples = 0;
+    let mut distinct: HashSet<&T> = HashSet::new();
+    for v in values {
+        samples += 1;
+        distinct.insert(v);
+    }
+    let hashes: Vec<u64> = distinct.iter().map(|v| hash_value(*v)).collect();
+
+    let mut buckets: HashMap<usize, usize> = HashMap::new();
+    for h in hashes.iter() {
+        *buckets.entry((*h as usize) % dim).or_insert(0) += 1;
+    }
+    let mut worst_buckets: Vec<(usize, usize)> = buckets
+        .iter()
+        .filter(|(_, c)| **c > 1)
+        .map(|(b, c)| (*b, *c))
+        .collect();
+    worst_buckets.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    worst_buckets.truncate(WORST_BUCKETS);
+
+    CollisionReport {
+        dim,
+        samples,
+        distinct: hashes.len(),
+        buckets_used: buckets.len(),
+        collision_rate: collision_rate(&hashes, dim),
+        worst_buckets,
+        target_rate,
+        recommended_dim: recommend_dim(&hashes, target_rate),
+    }
+}
+
+/// Same as `analyze`, with the dimension taken from a hashing trick featurizer,
+/// like the ones built by `make_string_feature!`.
+pub fn analyze_featurizer<'a, F, T, I>(values: I, target_rate: f64) -> CollisionReport
+where
+    F: Featurizer<T>,
+    T: Hash + Eq + 'a,
+    I: IntoIterator<Item = &'a T>,
+{
+    analyze(values, F::dim(), target_rate)
+}
+
+impl fmt::Display for CollisionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "samples:          {}", self.samples)?;
+        writeln!(f, "distinct values:  {}", self.distinct)?;
+        writeln!(f, "dimension:        {}", self.dim)?;
+        writeln!(f, "buckets used:     {}", self.buckets_used)?;
+        writeln!(f, "collision rate:   {:.4}", self.collision_rate)?;
+        write!(f, "worst buckets:   ")?;
+        if self.worst_buckets.is_empty() {
+            write!(f, " none")?;
+        }
+        for (bucket, count) in self.worst_buckets.iter() {
+            write!(f, " {} ({} values)", bucket, count)?;
+        }
+        writeln!(f)?;
+        write!(
+            f,
+            "recommended dim:  {} (collision rate <= {})",
+            self.recommended_dim, self.target_rate
+        )
+    }
+}
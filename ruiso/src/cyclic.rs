@@ -0,0 +1,46 @@
+//! # Cyclical encoding
+//!
+//! Periodic values like the hour of the day or a compass bearing are written as the sine and
+//! cosine of `2πx/period`, so 23:00 ends up next to 00:00. `StructFeature` does this for
+//! `#[struct_feature(cyclic = 24)]`, and `make_cyclic_feature!` builds a standalone featurizer.
+
+use std::f64::consts::PI;
+
+/// Writes `sin(2πx/period)` and `cos(2πx/period)` into the first two entries of the slice.
+#[inline]
+pub fn fill_cyclic(x: f64, period: f64, slice: &mut [f32]) {
+    let angle = 2.0 * PI * x / period;
+    slice[0] = angle.sin() as f32;
+    slice[1] = angle.cos() as f32;
+}
+
+/// Builds a cyclical featurizer with the desired name and period, for every numeric type.
+/// The name should end in 2, the dimension.
+/// ```
+/// # use ruiso::{make_cyclic_feature, Featurizer};
+/// make_cyclic_feature!(HourOfDay2, 24.0);
+/// # assert!(HourOfDay2::featurize(&0u32) == vec![0.0, 1.0]);
+/// ```
+#[macro_export]
+macro_rules! make_cyclic_feature {
+    ($name:ident,$period:expr) => {
+        #[derive(Debug)]
+        pub struct $name {}
+        $crate::make_cyclic_feature!(@impl $name, $period, f32, f64, u8, u16, u32, u64, i8, i16, i32, i64, usize);
+    };
+    (@impl $name:ident, $period:expr, $($native_ty:ty),*) => {
+        $(
+            impl $crate::Featurizer<$native_ty> for $name {
+                #[inline]
+                fn dim() -> usize {
+                    2
+                }
+                #[inline]
+                fn fill_slice(data: &$native_ty, slice: &mut [f32]) {
+                    $crate::cyclic::fill_cyclic(*data as f64, $period as f64, slice);
+                }
+                fn default(_slice: &mut [f32]) {}
+            }
+        )*
+    };
+}
@@ -0,0 +1,279 @@
+//! # Embeddings
+//!
+//! Dense vectors looked up in a pretrained table. `EmbeddingTable` reads GloVe and word2vec
+//! text exports, or raw little endian `f32` rows with an optional token list, and
+//! `make_embedding_feature!` builds the featurizer that looks values up in it.
+//! Like fitted state, the table lives in a process wide slot that has to be filled before featurizing.
+//!
+//! Strings are looked up by token and integer ids by row. Tokens missing from the table get
+//! the out of vocabulary vector, zeros unless set, and `Vec`s are averaged over their elements.
+//! Other types, like enums, can implement `Embeddable` by looking up one of their names.
+
+use crate::fitted::{invalid, parse};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::Path;
+
+/// # Embedding Table
+/// Rows of `dim` floats, with the token of every named row.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EmbeddingTable {
+    /// Width of every vector
+    pub dim: usize,
+    /// Vector written for unknown tokens and ids
+    pub oov: Vec<f32>,
+    vectors: Vec<f32>,
+    index: HashMap<String, usize>,
+}
+
+impl EmbeddingTable {
+    /// A table from row major `vectors`, naming row `j` after `tokens[j]`. Tokens can be left empty for id only tables.
+    pub fn new(dim: usize, vectors: Vec<f32>, tokens: Vec<String>) -> io::Result<Self> {
+        if dim == 0 || !vectors.len().is_multiple_of(dim) {
+            return Err(invalid(format!(
+                "{} floats don't make rows of {}",
+                vectors.len(),
+                dim
+            )));
+        }
+        if !tokens.is_empty() && tokens.len() != vectors.len() / dim {
+            return Err(invalid(format!(
+                "{} tokens for {} rows",
+                tokens.len(),
+                vectors.len() / dim
+            )));
+        }
+        let mut index = HashMap::with_capacity(tokens.len());
+        for (j, token) in tokens.into_iter().enumerate() {
+            index.entry(token).or_insert(j);
+        }
+        Ok(EmbeddingTable {
+            dim,
+            oov: vec![0.0; dim],
+            vectors,
+            index,
+        })
+    }
+
+    /// Reads a GloVe or word2vec text export, a token then its floats on every line, separated by spaces.
+    /// The `count dim` header of word2vec files is skipped.
+    pub fn read_text<R: BufRead>(r: R) -> io::Result<Self> {
+        let mut dim = 0;
+        let mut vectors = Vec::new();
+        let mut tokens = Vec::new();
+        for (n, line) in r.lines().enumerate() {
+            let line = line?;
+            let mut parts = line.trim_end().split(' ');
+            let token = match parts.next() {
+                Some(t) if !t.is_empty() => t.to_string(),
+                _ => continue,
+            };
+            let row = parts
+                .map(|x| parse(Some(x), "embedding value"))
+                .collect::<io::Result<Vec<f32>>>()?;
+            if n == 0 && row.len() == 1 && token.parse::<usize>().is_ok() {
+                continue;
+            }
+            if dim == 0 {
+                dim = row.len();
+            }
+            if row.len() != dim {
+                return Err(invalid(format!(
+                    "line {} has {} values instead of {}",
+                    n + 1,
+                    row.len(),
+                    dim
+                )));
+            }
+            tokens.push(token);
+            vectors.extend(row);
+        }
+        EmbeddingTable::new(dim, vectors, tokens)
+    }
+
+    /// Reads raw little endian `f32` rows of `dim` values. Rows are only reachable by id.
+    pub fn read_binary<R: Read>(mut r: R, dim: usize) -> io::Result<Self> {
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes)?;
+        if !bytes.len().is_multiple_of(4) {
+            return Err(invalid("binary embeddings aren't a whole number of f32"));
+        }
+        let vectors = bytes
+            .chunks_exact(4)
+            .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+        EmbeddingTable::new(dim, vectors, Vec::new())
+    }
+
+    /// Loads a text export
+    pub fn load_text<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        EmbeddingTable::read_text(BufReader::new(File::open(path)?))
+    }
+
+    /// Loads raw `f32` rows, naming them after the lines of `tokens` when given
+    pub fn load_binary<P: AsRef<Path>, Q: AsRef<Path>>(path: P, dim: usize, tokens: Option<Q>) -> io::Result<Self> {
+        let table = EmbeddingTable::read_binary(BufReader::new(File::open(path)?), dim)?;
+        match tokens {
+            Some(tokens) => {
+                let tokens = BufReader::new(File::open(tokens)?)
+                    .lines()
+                    .collect::<io::Result<Vec<String>>>()?;
+                EmbeddingTable::new(dim, table.vectors, tokens)
+            }
+            None => Ok(table),
+        }
+    }
+
+    /// Replaces the out of vocabulary vector. Panics if its width is wrong.
+    pub fn with_oov(mut self, oov: Vec<f32>) -> Self {
+        assert!(oov.len() == self.dim, "OOV vector should have {} values", self.dim);
+        self.oov = oov;
+        self
+    }
+
+    /// Number of rows
+    pub fn len(&self) -> usize {
+        self.vectors.len() / self.dim.max(1)
+    }
+
+    /// True if there are no rows
+    pub fn is_empty(&self) -> bool {
+        self.vectors.is_empty()
+    }
+
+    /// The vector of row `id`, the OOV vector past the last row
+    #[inline]
+    pub fn row(&self, id: usize) -> &[f32] {
+        if id < self.len() {
+            &self.vectors[id * self.dim..(id + 1) * self.dim]
+        } else {
+            &self.oov
+        }
+    }
+
+    /// The vector of `token`, the OOV vector if it isn't known
+    #[inline]
+    pub fn token(&self, token: &str) -> &[f32] {
+        match self.index.get(token) {
+            Some(id) => self.row(*id),
+            None => &self.oov,
+        }
+    }
+}
+
+/// # Embeddable
+/// Values that can be looked up in an `EmbeddingTable`.
+pub trait Embeddable {
+    /// Writes the vector of the value into a slice of `table.dim` floats
+    fn embed(&self, table: &EmbeddingTable, slice: &mut [f32]);
+}
+
+impl Embeddable for str {
+    #[inline]
+    fn embed(&self, table: &EmbeddingTable, slice: &mut [f32]) {
+        slice.copy_from_slice(table.token(self));
+    }
+}
+
+impl Embeddable for String {
+    #[inline]
+    fn embed(&self, table: &EmbeddingTable, slice: &mut [f32]) {
+        self.as_str().embed(table, slice);
+    }
+}
+
+impl<T: Embeddable + ?Sized> Embeddable for &T {
+    #[inline]
+    fn embed(&self, table: &EmbeddingTable, slice: &mut [f32]) {
+        (**self).embed(table, slice);
+    }
+}
+
+macro_rules! embeddable_id {
+    ($($t:ty),*) => {
+        $(
+            impl Embeddable for $t {
+                #[inline]
+                fn embed(&self, table: &EmbeddingTable, slice: &mut [f32]) {
+                    slice.copy_from_slice(table.row(*self as usize));
+                }
+            }
+        )*
+    };
+}
+
+embeddable_id!(u8, u16, u32, u64, usize);
+
+/// The average of the vectors of the elements, zeros when empty.
+impl<T: Embeddable> Embeddable for Vec<T> {
+    fn embed(&self, table: &EmbeddingTable, slice: &mut [f32]) {
+        if self.is_empty() {
+            return;
+        }
+        let mut one = vec![0.0; table.dim];
+        for element in self {
+            element.embed(table, &mut one);
+            for (s, v) in slice.iter_mut().zip(one.iter()) {
+                *s += *v;
+            }
+        }
+        let n = self.len() as f32;
+        for s in slice.iter_mut() {
+            *s /= n;
+        }
+    }
+}
+
+/// Builds an embedding featurizer with the desired name, for every `Embeddable`.
+/// The name should end in the width of the embedding, which the installed table has to match.
+/// ```no_run
+/// # use ruiso::embedding::EmbeddingTable;
+/// # use ruiso::make_embedding_feature;
+/// make_embedding_feature!(WordVec50, 50);
+/// # fn main() -> std::io::Result<()> {
+/// WordVec50::install(EmbeddingTable::load_text("glove.6B.50d.txt")?)?;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! make_embedding_feature {
+    ($name:ident, $dim:expr) => {
+        #[derive(Debug)]
+        pub struct $name {}
+        impl $name {
+            #[doc(hidden)]
+            pub fn ruiso_table() -> &'static $crate::fitted::FittedSlot<$crate::embedding::EmbeddingTable> {
+                static SLOT: $crate::fitted::FittedSlot<$crate::embedding::EmbeddingTable> =
+                    $crate::fitted::FittedSlot::new();
+                &SLOT
+            }
+            /// Installs the table for every following featurization.
+            pub fn install(table: $crate::embedding::EmbeddingTable) -> std::io::Result<()> {
+                if table.dim != $dim {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("{} needs vectors of {}, not {}", stringify!($name), $dim, table.dim),
+                    ));
+                }
+                Self::ruiso_table().install(table);
+                Ok(())
+            }
+        }
+        impl<T: $crate::embedding::Embeddable> $crate::Featurizer<T> for $name {
+            #[inline]
+            fn dim() -> usize {
+                $dim
+            }
+            #[inline]
+            fn fill_slice(data: &T, slice: &mut [f32]) {
+                let table = match $name::ruiso_table().get() {
+                    Some(table) => table,
+                    None => panic!("{} has no table, call {}::install first", stringify!($name), stringify!($name)),
+                };
+                data.embed(&table, slice);
+            }
+            fn default(_slice: &mut [f32]) {}
+        }
+    };
+}
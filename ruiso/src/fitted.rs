@@ -0,0 +1,385 @@
+//! # Fitted state
+//!
+//! Featurizers are zero sized types that are called but never created, so anything learned
+//! from data has to live somewhere else. A derived struct with fitted fields keeps its
+//! `FittedStruct` in a process wide `FittedSlot`, filled by `install_fitted` or `load_fitted`.
+//! Everything fitted can be written to and read back from a small text format with `Persist`,
+//! so training and serving use the exact same values.
+
+use crate::bucket::Buckets;
+use crate::frequency::CountEncoding;
+use crate::scaling::StandardStats;
+use crate::target::TargetEncoding;
+use crate::tfidf::TfIdf;
+use crate::vocab::Vocabulary;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, RwLock};
+
+const HEADER: &str = "ruiso-fitted";
+
+/// # Fitted Slot
+/// Holds the fitted state of a featurizer for the whole process.
+#[derive(Debug)]
+pub struct FittedSlot<T> {
+    inner: RwLock<Option<Arc<T>>>,
+}
+
+impl<T> FittedSlot<T> {
+    /// An empty slot, usable in a `static`
+    pub const fn new() -> Self {
+        FittedSlot {
+            inner: RwLock::new(None),
+        }
+    }
+
+    /// Replaces the fitted state. Calls already holding the old state keep using it.
+    pub fn install(&self, value: T) {
+        *self.inner.write().unwrap() = Some(Arc::new(value));
+    }
+
+    /// The fitted state, if there is one
+    pub fn get(&self) -> Option<Arc<T>> {
+        self.inner.read().unwrap().clone()
+    }
+
+    /// The fitted state. Panics with the name of the owner if nothing was installed.
+    pub fn expect(&self, owner: &str) -> Arc<T> {
+        match self.get() {
+            Some(fitted) => fitted,
+            None => panic!(
+                "{} has not been fitted, call {}::install_fitted or {}::load_fitted first",
+                owner, owner, owner
+            ),
+        }
+    }
+}
+
+impl<T> Default for FittedSlot<T> {
+    fn default() -> Self {
+        FittedSlot::new()
+    }
+}
+
+/// # Persist
+/// Saving and loading of fitted state as text.
+pub trait Persist: Sized {
+    /// Writes the state
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()>;
+    /// Reads back state written by `write_to`
+    fn read_from<R: BufRead>(r: &mut R) -> io::Result<Self>;
+    /// Writes the state to a file
+    fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write_to(&mut w)?;
+        w.flush()
+    }
+    /// Reads the state from a file
+    fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+}
+
+/// FNV-1a 64 bit hash of the UTF-8 bytes of a token. Unlike the hashing trick's hasher it is
+/// fixed, so fitted state saved per hashed column stays valid across builds.
+pub fn stable_hash(token: &str) -> u64 {
+    let mut hasher = StableHasher::default();
+    hasher.write(token.as_bytes());
+    hasher.finish()
+}
+
+/// `stable_hash` of any `Hash` value, fed through `StableHasher`
+pub fn stable_hash_of<T: Hash + ?Sized>(value: &T) -> u64 {
+    let mut hasher = StableHasher::default();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// # Stable Hasher
+/// FNV-1a 64 bit over the bytes written, with integers written little endian and `usize`/`isize`
+/// as 64 bits, so a value hashes the same on every platform and build.
+#[derive(Debug, Clone, Copy)]
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        StableHasher(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    fn write_u16(&mut self, i: u16) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_u32(&mut self, i: u32) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_u64(&mut self, i: u64) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_u128(&mut self, i: u128) {
+        self.write(&i.to_le_bytes());
+    }
+    fn write_usize(&mut self, i: usize) {
+        self.write_u64(i as u64);
+    }
+    fn write_i16(&mut self, i: i16) {
+        self.write_u16(i as u16);
+    }
+    fn write_i32(&mut self, i: i32) {
+        self.write_u32(i as u32);
+    }
+    fn write_i64(&mut self, i: i64) {
+        self.write_u64(i as u64);
+    }
+    fn write_i128(&mut self, i: i128) {
+        self.write_u128(i as u128);
+    }
+    fn write_isize(&mut self, i: isize) {
+        self.write_u64(i as i64 as u64);
+    }
+}
+
+/// Column of a token among `dim` hashed columns, by `stable_hash`
+#[inline]
+pub fn stable_bucket(token: &str, dim: usize) -> usize {
+    (stable_hash(token) % dim as u64) as usize
+}
+
+pub(crate) fn invalid<E: Into<Box<dyn std::error::Error + Send + Sync>>>(msg: E) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Reads a line without its line ending, failing at the end of the input.
+pub(crate) fn read_line<R: BufRead>(r: &mut R) -> io::Result<String> {
+    let mut line = String::new();
+    if r.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "fitted state ended early",
+        ));
+    }
+    while line.ends_with('\n') || line.ends_with('\r') {
+        line.pop();
+    }
+    Ok(line)
+}
+
+pub(crate) fn parse<T: std::str::FromStr>(s: Option<&str>, what: &str) -> io::Result<T> {
+    s.and_then(|s| s.parse().ok())
+        .ok_or_else(|| invalid(format!("could not read {} from fitted state", what)))
+}
+
+/// Escapes backslashes, tabs and line breaks so a string fits on one line.
+pub(crate) fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Reverses `escape`
+pub(crate) fn unescape(s: &str) -> io::Result<String> {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('\\') => out.push('\\'),
+                Some('n') => out.push('\n'),
+                Some('r') => out.push('\r'),
+                Some('t') => out.push('\t'),
+                other => return Err(invalid(format!("bad escape \\{:?} in fitted state", other))),
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    Ok(out)
+}
+
+/// # Fitted Field
+/// What was learned for one field of a derived struct.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FittedField {
+    /// Mean and standard deviation for `scale = "standard"`
+    Standard(StandardStats),
+    /// Bucket boundaries for `quantiles = n`
+    Quantiles(Buckets),
+    /// Tokens for `vocab(top_k = n)`
+    Vocab(Vocabulary),
+    /// Smoothed mean labels for `target`
+    Target(TargetEncoding),
+    /// Category counts for `count_encoding`
+    Counts(CountEncoding),
+    /// Inverse document frequencies for `tfidf`
+    TfIdf(TfIdf),
+}
+
+impl FittedField {
+    /// Name of the kind of field, as written in the saved state
+    pub fn kind(&self) -> &'static str {
+        match self {
+            FittedField::Standard(_) => "standard",
+            FittedField::Quantiles(_) => "quantiles",
+            FittedField::Vocab(_) => "vocab",
+            FittedField::Target(_) => "target",
+            FittedField::Counts(_) => "counts",
+            FittedField::TfIdf(_) => "tfidf",
+        }
+    }
+
+    /// Number of columns the field fills
+    pub fn width(&self) -> usize {
+        match self {
+            FittedField::Standard(_) => 1,
+            FittedField::Quantiles(buckets) => buckets.dim(),
+            FittedField::Vocab(vocab) => vocab.dim(),
+            FittedField::Target(_) | FittedField::Counts(_) => 1,
+            FittedField::TfIdf(tfidf) => tfidf.dim(),
+        }
+    }
+}
+
+/// # Fitted Struct
+/// Everything learned for a derived struct, one entry per fitted field in declaration order.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct FittedStruct {
+    /// (field name, fitted field) pairs
+    pub fields: Vec<(String, FittedField)>,
+}
+
+impl FittedStruct {
+    /// Checks that the fields match the (field name, kind, width) a struct expects.
+    pub fn check_schema(&self, schema: &[(&str, &str, usize)]) -> io::Result<()> {
+        let found: Vec<(&str, &str, usize)> = self
+            .fields
+            .iter()
+            .map(|(name, field)| (name.as_str(), field.kind(), field.width()))
+            .collect();
+        if found == schema {
+            Ok(())
+        } else {
+            Err(invalid(format!(
+                "fitted fields {:?} don't match the struct's {:?}",
+                found, schema
+            )))
+        }
+    }
+
+    /// Statistics of the `k`th fitted field
+    #[inline]
+    pub fn standard(&self, k: usize) -> &StandardStats {
+        match &self.fields[k].1 {
+            FittedField::Standard(stats) => stats,
+            other => panic!("fitted field {} is {}, not standard", k, other.kind()),
+        }
+    }
+
+    /// Buckets of the `k`th fitted field
+    #[inline]
+    pub fn quantiles(&self, k: usize) -> &Buckets {
+        match &self.fields[k].1 {
+            FittedField::Quantiles(buckets) => buckets,
+            other => panic!("fitted field {} is {}, not quantiles", k, other.kind()),
+        }
+    }
+
+    /// Vocabulary of the `k`th fitted field
+    #[inline]
+    pub fn vocab(&self, k: usize) -> &Vocabulary {
+        match &self.fields[k].1 {
+            FittedField::Vocab(vocab) => vocab,
+            other => panic!("fitted field {} is {}, not vocab", k, other.kind()),
+        }
+    }
+
+    /// Target encoding of the `k`th fitted field
+    #[inline]
+    pub fn target(&self, k: usize) -> &TargetEncoding {
+        match &self.fields[k].1 {
+            FittedField::Target(encoding) => encoding,
+            other => panic!("fitted field {} is {}, not target", k, other.kind()),
+        }
+    }
+
+    /// Count encoding of the `k`th fitted field
+    #[inline]
+    pub fn counts(&self, k: usize) -> &CountEncoding {
+        match &self.fields[k].1 {
+            FittedField::Counts(encoding) => encoding,
+            other => panic!("fitted field {} is {}, not counts", k, other.kind()),
+        }
+    }
+
+    /// TF-IDF weights of the `k`th fitted field
+    #[inline]
+    pub fn tfidf(&self, k: usize) -> &TfIdf {
+        match &self.fields[k].1 {
+            FittedField::TfIdf(tfidf) => tfidf,
+            other => panic!("fitted field {} is {}, not tfidf", k, other.kind()),
+        }
+    }
+}
+
+impl Persist for FittedStruct {
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{} {}", HEADER, self.fields.len())?;
+        for (name, field) in self.fields.iter() {
+            writeln!(w, "{} {}", name, field.kind())?;
+            match field {
+                FittedField::Standard(stats) => stats.write_to(w)?,
+                FittedField::Quantiles(buckets) => buckets.write_to(w)?,
+                FittedField::Vocab(vocab) => vocab.write_to(w)?,
+                FittedField::Target(encoding) => encoding.write_to(w)?,
+                FittedField::Counts(encoding) => encoding.write_to(w)?,
+                FittedField::TfIdf(tfidf) => tfidf.write_to(w)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn read_from<R: BufRead>(r: &mut R) -> io::Result<Self> {
+        let header = read_line(r)?;
+        let mut parts = header.split(' ');
+        if parts.next() != Some(HEADER) {
+            return Err(invalid("not a ruiso fitted state"));
+        }
+        let count: usize = parse(parts.next(), "field count")?;
+        let mut fields = Vec::with_capacity(count);
+        for _ in 0..count {
+            let line = read_line(r)?;
+            let mut parts = line.split(' ');
+            let name = parts.next().unwrap_or("").to_string();
+            let field = match parts.next() {
+                Some("standard") => FittedField::Standard(StandardStats::read_from(r)?),
+                Some("quantiles") => FittedField::Quantiles(Buckets::read_from(r)?),
+                Some("vocab") => FittedField::Vocab(Vocabulary::read_from(r)?),
+                Some("target") => FittedField::Target(TargetEncoding::read_from(r)?),
+                Some("counts") => FittedField::Counts(CountEncoding::read_from(r)?),
+                Some("tfidf") => FittedField::TfIdf(TfIdf::read_from(r)?),
+                other => return Err(invalid(format!("unknown fitted kind {:?}", other))),
+            };
+            fields.push((name, field));
+        }
+        Ok(FittedStruct { fields })
+    }
+}
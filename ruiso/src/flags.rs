@@ -0,0 +1,68 @@
+//! # Bit flags
+//!
+//! Packed flag words written as one column per bit. `StructFeature` does this for unsigned
+//! integer fields with `#[struct_feature(bits = 8)]`. With the `bitflags` feature, `bitflags` fields
+//! get one column per named flag with `#[struct_feature(flags = 4)]`, giving the number of flags, and
+//! `make_flags_feature!` builds a standalone featurizer doing the same.
+
+/// Writes bit `j` of `x` into `slice[j]`, for the lowest `n` bits.
+#[inline]
+pub fn fill_bits(x: u64, n: usize, slice: &mut [f32]) {
+    for (j, s) in slice.iter_mut().enumerate().take(n) {
+        if (x >> j) & 1 == 1 {
+            *s = 1.0;
+        }
+    }
+}
+
+/// Writes a 1 for every flag of `T::FLAGS` that is set, in declaration order.
+#[cfg(feature = "bitflags")]
+#[inline]
+pub fn fill_flags<T: bitflags::Flags>(data: &T, slice: &mut [f32]) {
+    for (j, flag) in T::FLAGS.iter().enumerate() {
+        if data.contains(T::from_bits_retain(flag.value().bits())) {
+            slice[j] = 1.0;
+        }
+    }
+}
+
+/// Names of the columns written by `fill_flags`
+#[cfg(feature = "bitflags")]
+pub fn flag_names<T: bitflags::Flags>() -> Vec<&'static str> {
+    T::FLAGS.iter().map(|flag| flag.name()).collect()
+}
+
+/// Builds a featurizer for a `bitflags` type, with one column per named flag.
+/// The name should end in the number of flags.
+/// ```
+/// # use ruiso::make_flags_feature;
+/// bitflags::bitflags! {
+///     pub struct TcpFlags: u8 {
+///         const FIN = 1;
+///         const SYN = 2;
+///     }
+/// }
+/// make_flags_feature!(TcpFlagsFeaturizer2, TcpFlags);
+/// ```
+#[cfg(feature = "bitflags")]
+#[macro_export]
+macro_rules! make_flags_feature {
+    ($name:ident,$flags_ty:ty) => {
+        #[derive(Debug)]
+        pub struct $name {}
+        impl $crate::Featurizer<$flags_ty> for $name {
+            #[inline]
+            fn dim() -> usize {
+                <$flags_ty as $crate::flags::Flags>::FLAGS.len()
+            }
+            #[inline]
+            fn fill_slice(data: &$flags_ty, slice: &mut [f32]) {
+                $crate::flags::fill_flags(data, slice);
+            }
+            fn default(_slice: &mut [f32]) {}
+        }
+    };
+}
+
+#[cfg(feature = "bitflags")]
+pub use bitflags::Flags;
@@ -0,0 +1,138 @@
+//! # Count encoding
+//!
+//! Replaces a category by how often it was seen in the training data, as a count or as a
+//! share of all rows, optionally as `ln(1 + x)`. `#[struct_feature(count_encoding(frequency, log, unseen = -1))]`
+//! fits it for a field. Like target encoding, categories are kept by their `fitted::stable_hash_of`
+//! so any `Hash` type can be encoded.
+
+use crate::fitted::{invalid, parse, read_line, stable_hash_of, Persist};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::{self, BufRead, Write};
+
+// Saved tables start with how their categories are hashed.
+const KEYS: &str = "fnv1a";
+
+/// # Count Encoding
+/// A fitted table of category counts, with how to write them.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CountEncoding {
+    /// Number of rows the counts were fitted on
+    pub total: u64,
+    /// Rows seen by the `stable_hash_of` of the category
+    pub counts: HashMap<u64, u64>,
+    /// Write the share of rows instead of the count
+    pub frequency: bool,
+    /// Write `ln(1 + x)` of the count or share
+    pub log: bool,
+    /// Value written for categories never seen
+    pub unseen: f64,
+}
+
+impl CountEncoding {
+    /// The encoded value of a category
+    #[inline]
+    pub fn encode<T: Hash + ?Sized>(&self, category: &T) -> f64 {
+        let count = match self.counts.get(&stable_hash_of(category)) {
+            Some(c) => *c as f64,
+            None => return self.unseen,
+        };
+        let v = if self.frequency {
+            count / self.total as f64
+        } else {
+            count
+        };
+        if self.log {
+            v.ln_1p()
+        } else {
+            v
+        }
+    }
+}
+
+impl Persist for CountEncoding {
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(
+            w,
+            "{} {} {} {} {} {}",
+            KEYS,
+            if self.frequency { "frequency" } else { "count" },
+            if self.log { "log" } else { "raw" },
+            self.unseen,
+            self.total,
+            self.counts.len()
+        )?;
+        let mut entries: Vec<(&u64, &u64)> = self.counts.iter().collect();
+        entries.sort_by_key(|(h, _)| **h);
+        for (h, c) in entries {
+            writeln!(w, "{} {}", h, c)?;
+        }
+        Ok(())
+    }
+
+    fn read_from<R: BufRead>(r: &mut R) -> io::Result<Self> {
+        let line = read_line(r)?;
+        let mut parts = line.split(' ');
+        if parts.next() != Some(KEYS) {
+            return Err(invalid(format!("count encoding categories should be keyed by {}", KEYS)));
+        }
+        let frequency = match parts.next() {
+            Some("frequency") => true,
+            Some("count") => false,
+            other => return Err(invalid(format!("unknown count scale {:?}", other))),
+        };
+        let log = match parts.next() {
+            Some("log") => true,
+            Some("raw") => false,
+            other => return Err(invalid(format!("unknown count transform {:?}", other))),
+        };
+        let unseen = parse(parts.next(), "unseen value")?;
+        let total = parse(parts.next(), "total")?;
+        let len: usize = parse(parts.next(), "category count")?;
+        let mut counts = HashMap::with_capacity(len);
+        for _ in 0..len {
+            let line = read_line(r)?;
+            let mut parts = line.split(' ');
+            let h: u64 = parse(parts.next(), "category hash")?;
+            let c: u64 = parse(parts.next(), "category count")?;
+            if counts.insert(h, c).is_some() {
+                return Err(invalid("repeated category in count encoding"));
+            }
+        }
+        Ok(CountEncoding {
+            total,
+            counts,
+            frequency,
+            log,
+            unseen,
+        })
+    }
+}
+
+/// # Category Counter
+/// Counts categories to fit a `CountEncoding`.
+#[derive(Debug, Clone, Default)]
+pub struct CategoryCounter {
+    total: u64,
+    counts: HashMap<u64, u64>,
+}
+
+impl CategoryCounter {
+    /// Adds one row of `category`
+    #[inline]
+    pub fn observe<T: Hash + ?Sized>(&mut self, category: &T) {
+        self.total += 1;
+        *self.counts.entry(stable_hash_of(category)).or_insert(0) += 1;
+    }
+
+    /// The table of everything observed so far
+    pub fn finish(&self, frequency: bool, log: bool, unseen: f64) -> CountEncoding {
+        CountEncoding {
+            total: self.total,
+            counts: self.counts.clone(),
+            frequency,
+            log,
+            unseen,
+        }
+    }
+}
@@ -15,6 +15,29 @@ extern crate ruiso_derive;
 pub use ruiso_derive::*;
 pub use std::hash::{Hash, Hasher};
 
+pub mod bucket;
+pub mod bytes;
+pub mod collision;
+pub mod cyclic;
+pub mod embedding;
+pub mod fitted;
+pub mod flags;
+pub mod frequency;
+pub mod ngram;
+pub mod numeric;
+pub mod path;
+pub mod patterns;
+pub mod scaling;
+pub mod string_stats;
+pub mod target;
+pub mod text;
+pub mod tfidf;
+pub mod time;
+pub mod transform;
+pub mod vocab;
+
+pub use fitted::{FittedStruct, Persist};
+
 /// # Featurizer
 /// Implement this for a custom featurizer.
 /// This must end in a the dimension, Bla34, and it needs to be a zero sized type as it's called but not created.
@@ -0,0 +1,195 @@
+//! # N-grams
+//!
+//! Hashes every run of `n` characters or words of a text, for a range of `n`, so command lines,
+//! domains and user agents that share pieces land in shared columns.
+//! `#[struct_feature(ngrams(chars, min_n = 3, max_n = 5, dim = 1024, boundary))]` hashes a field
+//! and `make_ngram_feature!` builds a standalone featurizer.
+
+use crate::collision::hash_bucket;
+use crate::text::Tokenizer;
+
+/// Marker put before the text by `boundary`, to tell prefixes from inner n-grams
+pub const START: char = '<';
+/// Marker put after the text by `boundary`
+pub const END: char = '>';
+
+/// # N-gram Unit
+/// What an n-gram is made of.
+#[derive(Debug, Clone)]
+pub enum NgramUnit {
+    /// Characters
+    Chars,
+    /// Tokens of the tokenizer, joined with a space
+    Words(Tokenizer),
+}
+
+/// # N-gram Hasher
+/// Hashes the n-grams of a text for every `n` from `min_n` to `max_n`.
+#[derive(Debug, Clone)]
+pub struct NgramHasher {
+    /// Characters or words
+    pub unit: NgramUnit,
+    /// Shortest n-gram
+    pub min_n: usize,
+    /// Longest n-gram
+    pub max_n: usize,
+    /// Markers wrapped around the text, as characters for character n-grams and as extra tokens for words
+    pub boundary: Option<(String, String)>,
+    /// Lowercase the text first
+    pub lowercase: bool,
+    /// Set columns to 1 instead of counting
+    pub binary: bool,
+}
+
+impl NgramHasher {
+    /// Character n-grams from `min_n` to `max_n` characters
+    pub fn chars(min_n: usize, max_n: usize) -> Self {
+        NgramHasher::new(NgramUnit::Chars, min_n, max_n)
+    }
+
+    /// Word n-grams from `min_n` to `max_n` tokens
+    pub fn words(tokenizer: Tokenizer, min_n: usize, max_n: usize) -> Self {
+        NgramHasher::new(NgramUnit::Words(tokenizer), min_n, max_n)
+    }
+
+    fn new(unit: NgramUnit, min_n: usize, max_n: usize) -> Self {
+        assert!(
+            0 < min_n && min_n <= max_n,
+            "N-grams need 0 < min_n <= max_n, found {} and {}",
+            min_n,
+            max_n
+        );
+        NgramHasher {
+            unit,
+            min_n,
+            max_n,
+            boundary: None,
+            lowercase: false,
+            binary: false,
+        }
+    }
+
+    /// Wraps the text in `START` and `END` markers, or `<s>` and `</s>` tokens for words
+    pub fn boundary(self) -> Self {
+        match self.unit {
+            NgramUnit::Chars => self.markers(START.to_string(), END.to_string()),
+            NgramUnit::Words(_) => self.markers("<s>".to_string(), "</s>".to_string()),
+        }
+    }
+
+    /// Wraps the text in the given markers
+    pub fn markers<S: Into<String>>(mut self, start: S, end: S) -> Self {
+        self.boundary = Some((start.into(), end.into()));
+        self
+    }
+
+    /// Lowercases the text
+    pub fn lowercase(mut self) -> Self {
+        self.lowercase = true;
+        self
+    }
+
+    /// Marks the n-grams present instead of counting them
+    pub fn binary(mut self) -> Self {
+        self.binary = true;
+        self
+    }
+
+    /// Calls `f` with every n-gram of `text`
+    pub fn for_each<F: FnMut(&str)>(&self, text: &str, mut f: F) {
+        let text = if self.lowercase {
+            text.to_lowercase()
+        } else {
+            text.to_string()
+        };
+        match &self.unit {
+            NgramUnit::Chars => {
+                let padded = match &self.boundary {
+                    Some((start, end)) => format!("{}{}{}", start, text, end),
+                    None => text,
+                };
+                let mut bounds: Vec<usize> = padded.char_indices().map(|(b, _)| b).collect();
+                bounds.push(padded.len());
+                let chars = bounds.len() - 1;
+                for n in self.min_n..=self.max_n {
+                    for j in 0..(chars + 1).saturating_sub(n) {
+                        f(&padded[bounds[j]..bounds[j + n]]);
+                    }
+                }
+            }
+            NgramUnit::Words(tokenizer) => {
+                let mut tokens: Vec<&str> = Vec::new();
+                if let Some((start, _)) = &self.boundary {
+                    tokens.push(start);
+                }
+                tokens.extend(tokenizer.split(&text));
+                if let Some((_, end)) = &self.boundary {
+                    tokens.push(end);
+                }
+                let mut gram = String::new();
+                for n in self.min_n..=self.max_n {
+                    for window in tokens.windows(n) {
+                        gram.clear();
+                        for (j, token) in window.iter().enumerate() {
+                            if j > 0 {
+                                gram.push(' ');
+                            }
+                            gram.push_str(token);
+                        }
+                        f(&gram);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Hashes every n-gram into the columns of the slice
+    #[inline]
+    pub fn fill(&self, text: &str, slice: &mut [f32]) {
+        let dim = slice.len();
+        self.for_each(text, |gram| {
+            let j = hash_bucket(gram, dim);
+            if self.binary {
+                slice[j] = 1.0;
+            } else {
+                slice[j] += 1.0;
+            }
+        });
+    }
+}
+
+/// Builds a featurizer hashing the n-grams of strings with an `NgramHasher`, built once on first use.
+/// The name should end in the number of columns.
+/// ```
+/// # use ruiso::ngram::NgramHasher;
+/// # use ruiso::text::Tokenizer;
+/// # use ruiso::{make_ngram_feature, Featurizer};
+/// make_ngram_feature!(Domain1024, 1024, NgramHasher::chars(3, 5).boundary());
+/// make_ngram_feature!(Bigrams256, 256, NgramHasher::words(Tokenizer::Words, 2, 2).binary());
+/// # assert!(Domain1024::featurize(&"example.com").iter().sum::<f32>() == 11.0 + 10.0 + 9.0);
+/// ```
+#[macro_export]
+macro_rules! make_ngram_feature {
+    ($name:ident, $dim:expr, $hasher:expr) => {
+        #[derive(Debug)]
+        pub struct $name {}
+        impl $name {
+            #[doc(hidden)]
+            pub fn ruiso_ngrams() -> &'static $crate::ngram::NgramHasher {
+                static NGRAMS: std::sync::OnceLock<$crate::ngram::NgramHasher> = std::sync::OnceLock::new();
+                NGRAMS.get_or_init(|| $hasher)
+            }
+        }
+        impl<T: AsRef<str>> $crate::Featurizer<T> for $name {
+            #[inline]
+            fn dim() -> usize {
+                $dim
+            }
+            #[inline]
+            fn fill_slice(data: &T, slice: &mut [f32]) {
+                $name::ruiso_ngrams().fill(data.as_ref(), slice);
+            }
+            fn default(_slice: &mut [f32]) {}
+        }
+    };
+}
@@ -0,0 +1,50 @@
+//! # Numeric edge cases
+//!
+//! Casting to `f32` lets NaN and infinities through and silently rounds large integers.
+//! `StructFeature` uses these for `#[struct_feature(non_finite = "...")]` and
+//! `#[struct_feature(int_encoding = "...")]`.
+
+/// Bits kept in each of the lower parts of `split_int`. An `f32` holds integers up to 2^24 exactly.
+pub const SPLIT_BITS: u32 = 24;
+/// Number of columns written by `split_int`
+pub const SPLIT_PARTS: usize = 3;
+
+/// `x` if it is finite, `fallback` otherwise
+#[inline]
+pub fn finite_or(x: f32, fallback: f32) -> f32 {
+    if x.is_finite() {
+        x
+    } else {
+        fallback
+    }
+}
+
+/// Clamps infinities to the largest finite `f32` of the same sign. NaN becomes 0.
+#[inline]
+pub fn clamp_finite(x: f32) -> f32 {
+    if x.is_nan() {
+        0.0
+    } else {
+        x.clamp(f32::MIN, f32::MAX)
+    }
+}
+
+/// `x` if it is finite, panics naming the field otherwise
+#[inline]
+pub fn expect_finite(x: f32, field: &str) -> f32 {
+    if !x.is_finite() {
+        panic!("Field {} featurized to {}", field, x);
+    }
+    x
+}
+
+/// Splits an integer into `[high, mid, low]` with `x = high * 2^48 + mid * 2^24 + low` and
+/// `0 <= mid, low < 2^24`. Every part is exact for the whole range of `i64` and `u64`, where `high`
+/// stays within `-2^15..2^16`.
+#[inline]
+pub fn split_int(x: i128) -> [f32; SPLIT_PARTS] {
+    let base = 1i128 << SPLIT_BITS;
+    let low = x.rem_euclid(base);
+    let rest = x.div_euclid(base);
+    [rest.div_euclid(base) as f32, rest.rem_euclid(base) as f32, low as f32]
+}
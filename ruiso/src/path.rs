@@ -0,0 +1,204 @@
+//! # Paths
+//!
+//! File paths split into the pieces that matter, where hashing the whole path would only match exact
+//! duplicates: the depth, the extension, the directories, the file name, whether the file is hidden and
+//! whether it sits in a well known location like `/tmp`. Both `/` and `\` separate components, so
+//! Windows paths work too.
+//!
+//! `#[struct_feature(path(extension = 16, dirs = 64, basename = 64))]` featurizes `String` and `PathBuf`
+//! fields and `make_path_feature!` builds a standalone featurizer.
+
+use crate::collision::hash_bucket;
+use crate::vocab::Vocabulary;
+use std::path::Path;
+
+/// Names of the columns written before the extension block, in order
+pub const PATH_NAMES: [&str; 5] = ["depth", "hidden", "tmp", "dev_shm", "home"];
+
+/// Directories flagged by the `tmp` column
+pub const TMP_DIRS: [&str; 4] = ["/tmp", "/var/tmp", "/windows/temp", "/users/*/appdata/local/temp"];
+/// Directories flagged by the `dev_shm` column
+pub const SHM_DIRS: [&str; 1] = ["/dev/shm"];
+/// Directories flagged by the `home` column
+pub const HOME_DIRS: [&str; 3] = ["/home", "/root", "/users"];
+
+/// # Path Extension
+/// How the extension is written.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathExtension {
+    /// Hashed into this many buckets
+    Hashed(usize),
+    /// One column per extension of the vocabulary, plus one for the others
+    Vocab(Vocabulary),
+}
+
+impl PathExtension {
+    /// Number of columns
+    pub fn dim(&self) -> usize {
+        match self {
+            PathExtension::Hashed(dim) => *dim,
+            PathExtension::Vocab(vocab) => vocab.dim(),
+        }
+    }
+}
+
+/// # Path Featurizer
+/// Writes `PATH_NAMES`, then blocks for the extension, the directories and the file name.
+/// The extension is lowercased and files without one leave its block empty. Every directory
+/// adds one to its hashed column and the file name is one hot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathFeaturizer {
+    /// Columns of the extension
+    pub extension: PathExtension,
+    /// Hashed columns of the directories
+    pub dirs: usize,
+    /// Hashed columns of the file name
+    pub basename: usize,
+}
+
+impl Default for PathFeaturizer {
+    fn default() -> Self {
+        PathFeaturizer::new(16, 64, 64)
+    }
+}
+
+// Lowercased with `/` separators and without a drive letter, to compare against the well known directories.
+fn normalize(path: &str) -> String {
+    let path = path.replace('\\', "/").to_lowercase();
+    let bytes = path.as_bytes();
+    if bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' {
+        path[2..].to_string()
+    } else {
+        path
+    }
+}
+
+// Whether `path` is `dir` or below it, with `*` matching one component.
+fn under(path: &str, dir: &str) -> bool {
+    let mut parts = path.split('/');
+    for want in dir.split('/') {
+        match parts.next() {
+            Some(part) if want == "*" || part == want => {}
+            _ => return false,
+        }
+    }
+    true
+}
+
+impl PathFeaturizer {
+    /// Hashed blocks of the given sizes
+    pub fn new(extension: usize, dirs: usize, basename: usize) -> Self {
+        assert!(
+            extension > 0 && dirs > 0 && basename > 0,
+            "The dimension of a hashing featurizer can't be 0"
+        );
+        PathFeaturizer {
+            extension: PathExtension::Hashed(extension),
+            dirs,
+            basename,
+        }
+    }
+
+    /// Writes the extension as one of the given ones, lowercase and without the dot
+    pub fn extension_vocab(mut self, vocab: Vocabulary) -> Self {
+        self.extension = PathExtension::Vocab(vocab);
+        self
+    }
+
+    /// Number of columns
+    pub fn dim(&self) -> usize {
+        PATH_NAMES.len() + self.extension.dim() + self.dirs + self.basename
+    }
+
+    /// Names of the columns: `PATH_NAMES`, then `ext:exe` for every extension of a vocabulary and
+    /// `ext:other`, or `ext[j]` when hashed, then `dirs[j]` and `basename[j]`.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = PATH_NAMES.iter().map(|name| name.to_string()).collect();
+        match &self.extension {
+            PathExtension::Hashed(dim) => names.extend((0..*dim).map(|j| format!("ext[{}]", j))),
+            PathExtension::Vocab(vocab) => {
+                names.extend(vocab.tokens.iter().map(|token| format!("ext:{}", token)));
+                names.extend((vocab.tokens.len()..vocab.capacity).map(|j| format!("ext[{}]", j)));
+                names.push("ext:other".to_string());
+            }
+        }
+        names.extend((0..self.dirs).map(|j| format!("dirs[{}]", j)));
+        names.extend((0..self.basename).map(|j| format!("basename[{}]", j)));
+        names
+    }
+
+    /// Fills the columns for a path
+    pub fn fill<P: AsRef<Path>>(&self, path: P, slice: &mut [f32]) {
+        let path = path.as_ref().to_string_lossy();
+        let mut components: Vec<&str> = path.split(['/', '\\']).filter(|c| !c.is_empty()).collect();
+        if components.first().is_some_and(|c| c.len() == 2 && c.ends_with(':')) {
+            components.remove(0);
+        }
+        let basename = components.pop();
+        let normalized = normalize(&path);
+        let flagged = |dirs: &[&str]| dirs.iter().any(|dir| under(&normalized, dir));
+        let hidden = basename.is_some_and(|b| b.starts_with('.') && b != "." && b != "..");
+        slice[0] = (components.len() + basename.iter().count()) as f32;
+        slice[1] = hidden as u8 as f32;
+        slice[2] = flagged(&TMP_DIRS) as u8 as f32;
+        slice[3] = flagged(&SHM_DIRS) as u8 as f32;
+        slice[4] = flagged(&HOME_DIRS) as u8 as f32;
+
+        let mut j = PATH_NAMES.len();
+        let extension = basename
+            .and_then(|b| b.rfind('.').filter(|dot| *dot > 0).map(|dot| b[dot + 1..].to_lowercase()))
+            .filter(|ext| !ext.is_empty());
+        if let Some(ext) = extension {
+            match &self.extension {
+                PathExtension::Hashed(dim) => slice[j + hash_bucket(&ext, *dim)] = 1.0,
+                PathExtension::Vocab(vocab) => slice[j + vocab.index(&ext)] = 1.0,
+            }
+        }
+        j += self.extension.dim();
+        for dir in &components {
+            slice[j + hash_bucket(*dir, self.dirs)] += 1.0;
+        }
+        j += self.dirs;
+        if let Some(b) = basename {
+            slice[j + hash_bucket(b, self.basename)] = 1.0;
+        }
+    }
+}
+
+/// Builds a featurizer for `String` and `PathBuf` paths with a `PathFeaturizer`, built once on first use.
+/// The name should end in the number of columns, `PathFeaturizer::dim`.
+/// ```
+/// # use ruiso::path::PathFeaturizer;
+/// # use ruiso::{make_path_feature, Featurizer};
+/// make_path_feature!(Image149, 149, PathFeaturizer::new(16, 64, 64));
+/// # assert!(Image149::featurize(&"/tmp/x.sh")[..5] == [2.0, 0.0, 1.0, 0.0, 0.0]);
+/// ```
+#[macro_export]
+macro_rules! make_path_feature {
+    ($name:ident, $dim:expr, $path:expr) => {
+        #[derive(Debug)]
+        pub struct $name {}
+        impl $name {
+            #[doc(hidden)]
+            pub fn ruiso_path() -> &'static $crate::path::PathFeaturizer {
+                static PATH: std::sync::OnceLock<$crate::path::PathFeaturizer> = std::sync::OnceLock::new();
+                PATH.get_or_init(|| {
+                    let path = $path;
+                    assert!(path.dim() == $dim, "{} has {} columns, not {}", stringify!($name), path.dim(), $dim);
+                    path
+                })
+            }
+        }
+        impl<T: AsRef<std::path::Path>> $crate::Featurizer<T> for $name {
+            #[inline]
+            fn dim() -> usize {
+                $dim
+            }
+            #[inline]
+            fn fill_slice(data: &T, slice: &mut [f32]) {
+                $name::ruiso_path().fill(data, slice);
+            }
+            fn default(_slice: &mut [f32]) {}
+        }
+    };
+}
@@ -0,0 +1,139 @@
+//! # Patterns
+//!
+//! One column per keyword or regex, marking whether a text matches it or counting the matches,
+//! for lists of suspicious command line fragments and the like. Keywords are matched all at once
+//! with Aho-Corasick and regexes with a `RegexSet`, both compiled once.
+//! `#[struct_feature(keywords = "keywords.txt")]` and `#[struct_feature(regex = ["^powershell", "base64"])]`
+//! match a field and `make_pattern_feature!` builds a standalone featurizer.
+
+use aho_corasick::AhoCorasick;
+use regex::{Regex, RegexSet};
+
+/// # Pattern Set
+/// How the patterns are matched.
+#[derive(Debug, Clone)]
+pub enum PatternSet {
+    /// Plain substrings
+    Keywords(AhoCorasick),
+    /// Regexes, with each one compiled alone as well to count its matches
+    Regex(RegexSet, Vec<Regex>),
+}
+
+/// # Pattern Matcher
+/// Writes one column per pattern, in the order they were given.
+#[derive(Debug, Clone)]
+pub struct PatternMatcher {
+    /// The compiled patterns
+    pub set: PatternSet,
+    /// The patterns as given, naming the columns
+    pub patterns: Vec<String>,
+    /// Count the matches instead of marking the patterns found
+    pub count: bool,
+}
+
+impl PatternMatcher {
+    /// Matches keywords as substrings. Overlapping keywords all match.
+    pub fn keywords<S: Into<String>, I: IntoIterator<Item = S>>(keywords: I) -> Self {
+        let patterns: Vec<String> = keywords.into_iter().map(Into::into).collect();
+        let set = match AhoCorasick::new(&patterns) {
+            Ok(ac) => ac,
+            Err(e) => panic!("Can't build the keyword matcher: {}", e),
+        };
+        PatternMatcher {
+            set: PatternSet::Keywords(set),
+            patterns,
+            count: false,
+        }
+    }
+
+    /// Matches regexes. Panics if one doesn't compile.
+    pub fn regex<S: Into<String>, I: IntoIterator<Item = S>>(patterns: I) -> Self {
+        let patterns: Vec<String> = patterns.into_iter().map(Into::into).collect();
+        let set = match RegexSet::new(&patterns) {
+            Ok(set) => set,
+            Err(e) => panic!("Bad match pattern: {}", e),
+        };
+        let regexes = patterns.iter().map(|p| Regex::new(p).unwrap()).collect();
+        PatternMatcher {
+            set: PatternSet::Regex(set, regexes),
+            patterns,
+            count: false,
+        }
+    }
+
+    /// Counts the matches of every pattern
+    pub fn count(mut self) -> Self {
+        self.count = true;
+        self
+    }
+
+    /// Number of patterns, and of columns
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    /// No patterns at all
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Marks the patterns found in `text`, or adds their number of matches when counting
+    #[inline]
+    pub fn fill(&self, text: &str, slice: &mut [f32]) {
+        match &self.set {
+            PatternSet::Keywords(ac) => {
+                for m in ac.find_overlapping_iter(text) {
+                    let j = m.pattern().as_usize();
+                    if self.count {
+                        slice[j] += 1.0;
+                    } else {
+                        slice[j] = 1.0;
+                    }
+                }
+            }
+            PatternSet::Regex(set, regexes) => {
+                for j in set.matches(text).iter() {
+                    if self.count {
+                        slice[j] += regexes[j].find_iter(text).count() as f32;
+                    } else {
+                        slice[j] = 1.0;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Builds a featurizer matching strings against a `PatternMatcher`, built once on first use.
+/// The name should end in the number of patterns.
+/// ```
+/// # use ruiso::patterns::PatternMatcher;
+/// # use ruiso::{make_pattern_feature, Featurizer};
+/// make_pattern_feature!(Suspicious2, 2, PatternMatcher::regex(vec!["^powershell", "base64"]).count());
+/// # assert!(Suspicious2::featurize(&"powershell -e base64") == vec![1.0, 1.0]);
+/// ```
+#[macro_export]
+macro_rules! make_pattern_feature {
+    ($name:ident, $dim:expr, $matcher:expr) => {
+        #[derive(Debug)]
+        pub struct $name {}
+        impl $name {
+            #[doc(hidden)]
+            pub fn ruiso_patterns() -> &'static $crate::patterns::PatternMatcher {
+                static PATTERNS: std::sync::OnceLock<$crate::patterns::PatternMatcher> = std::sync::OnceLock::new();
+                PATTERNS.get_or_init(|| $matcher)
+            }
+        }
+        impl<T: AsRef<str>> $crate::Featurizer<T> for $name {
+            #[inline]
+            fn dim() -> usize {
+                $dim
+            }
+            #[inline]
+            fn fill_slice(data: &T, slice: &mut [f32]) {
+                $name::ruiso_patterns().fill(data.as_ref(), slice);
+            }
+            fn default(_slice: &mut [f32]) {}
+        }
+    };
+}
@@ -0,0 +1,84 @@
+//! # Standard scaling
+//!
+//! Backs `#[struct_feature(scale = "standard")]`: the mean and standard deviation of a field
+//! are fitted over the training data and fields are written as `(x - mean) / std`.
+
+use crate::fitted::{parse, read_line, Persist};
+use std::io::{self, BufRead, Write};
+
+/// # Running Stats
+/// Accumulates the mean and variance of a field in one pass.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RunningStats {
+    count: u64,
+    mean: f64,
+    m2: f64,
+}
+
+impl RunningStats {
+    /// Adds a value. NaN and infinities are skipped.
+    #[inline]
+    pub fn observe(&mut self, x: f64) {
+        if !x.is_finite() {
+            return;
+        }
+        self.count += 1;
+        let delta = x - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (x - self.mean);
+    }
+
+    /// The statistics of everything observed so far
+    pub fn finish(&self) -> StandardStats {
+        let std = if self.count > 0 {
+            (self.m2 / self.count as f64).sqrt()
+        } else {
+            0.0
+        };
+        StandardStats {
+            count: self.count,
+            mean: self.mean,
+            std,
+        }
+    }
+}
+
+/// # Standard Stats
+/// Fitted mean and standard deviation of a field.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct StandardStats {
+    /// Number of values the statistics were fitted on
+    pub count: u64,
+    /// Mean of the values
+    pub mean: f64,
+    /// Population standard deviation of the values
+    pub std: f64,
+}
+
+impl StandardStats {
+    /// Scales a value. Constant fields are only centered.
+    #[inline]
+    pub fn apply(&self, x: f64) -> f64 {
+        if self.std > 0.0 {
+            (x - self.mean) / self.std
+        } else {
+            x - self.mean
+        }
+    }
+}
+
+impl Persist for StandardStats {
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{} {} {}", self.count, self.mean, self.std)
+    }
+
+    fn read_from<R: BufRead>(r: &mut R) -> io::Result<Self> {
+        let line = read_line(r)?;
+        let mut parts = line.split(' ');
+        Ok(StandardStats {
+            count: parse(parts.next(), "count")?,
+            mean: parse(parts.next(), "mean")?,
+            std: parse(parts.next(), "standard deviation")?,
+        })
+    }
+}
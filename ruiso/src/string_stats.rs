@@ -0,0 +1,109 @@
+//! # String statistics
+//!
+//! Shape features of a string that don't depend on its exact content, like how random it looks.
+//! `StringStats` writes them as a fixed block of `STRING_STATS_NAMES.len()` columns, and
+//! `#[struct_feature(string_stats)]` uses it for a field.
+
+use std::collections::{HashMap, HashSet};
+
+/// Names of the columns written by `fill_string_stats`, in order
+pub const STRING_STATS_NAMES: [&str; 8] = [
+    "length",
+    "entropy",
+    "digit_ratio",
+    "upper_ratio",
+    "punct_ratio",
+    "non_ascii_ratio",
+    "longest_consonant_run",
+    "distinct_chars",
+];
+
+/// Shannon entropy of the characters of `s`, in bits per character
+pub fn shannon_entropy(s: &str) -> f64 {
+    let mut counts: HashMap<char, usize> = HashMap::new();
+    let mut total = 0;
+    for c in s.chars() {
+        *counts.entry(c).or_insert(0) += 1;
+        total += 1;
+    }
+    counts
+        .values()
+        .map(|n| {
+            let p = *n as f64 / total as f64;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Length of the longest run of ASCII consonants, `y` included
+pub fn longest_consonant_run(s: &str) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+    for c in s.chars() {
+        if c.is_ascii_alphabetic() && !matches!(c.to_ascii_lowercase(), 'a' | 'e' | 'i' | 'o' | 'u') {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+    longest
+}
+
+/// Fills the columns named by `STRING_STATS_NAMES`. Lengths count characters and ratios are 0 for empty strings.
+pub fn fill_string_stats(s: &str, slice: &mut [f32]) {
+    let mut length = 0;
+    let mut digits = 0;
+    let mut upper = 0;
+    let mut punct = 0;
+    let mut non_ascii = 0;
+    let mut distinct = HashSet::new();
+    for c in s.chars() {
+        length += 1;
+        if c.is_ascii_digit() {
+            digits += 1;
+        }
+        if c.is_uppercase() {
+            upper += 1;
+        }
+        if c.is_ascii_punctuation() {
+            punct += 1;
+        }
+        if !c.is_ascii() {
+            non_ascii += 1;
+        }
+        distinct.insert(c);
+    }
+    let ratio = |n: usize| {
+        if length > 0 {
+            n as f32 / length as f32
+        } else {
+            0.0
+        }
+    };
+    slice[0] = length as f32;
+    slice[1] = shannon_entropy(s) as f32;
+    slice[2] = ratio(digits);
+    slice[3] = ratio(upper);
+    slice[4] = ratio(punct);
+    slice[5] = ratio(non_ascii);
+    slice[6] = longest_consonant_run(s) as f32;
+    slice[7] = distinct.len() as f32;
+}
+
+/// # String Stats
+/// Featurizer writing the statistics of a string.
+#[derive(Debug)]
+pub struct StringStats {}
+
+impl<T: AsRef<str>> crate::Featurizer<T> for StringStats {
+    #[inline]
+    fn dim() -> usize {
+        STRING_STATS_NAMES.len()
+    }
+    #[inline]
+    fn fill_slice(data: &T, slice: &mut [f32]) {
+        fill_string_stats(data.as_ref(), slice);
+    }
+    fn default(_slice: &mut [f32]) {}
+}
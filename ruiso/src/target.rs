@@ -0,0 +1,174 @@
+//! # Target encoding
+//!
+//! Replaces a high cardinality category by the smoothed mean of the label over the training
+//! rows sharing it, `(sum + prior * prior_weight) / (count + prior_weight)`. Categories seen
+//! fewer than `min_count` times, or never, get the prior, the mean label of all rows.
+//! `#[struct_feature(target(prior_weight = 10, min_count = 2))]` fits it for a field from
+//! labeled data. Categories are kept by their `fitted::stable_hash_of`, so any `Hash` type can be
+//! encoded and saved tables stay valid across builds and platforms.
+//!
+//! Encoding the rows a table was fitted on leaks their label into the feature. `out_of_fold`
+//! and the generated `featurize_out_of_fold` fit on the other folds for every row instead.
+
+use crate::fitted::{invalid, parse, read_line, stable_hash_of, Persist};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io::{self, BufRead, Write};
+
+// Saved tables start with how their categories are hashed.
+const KEYS: &str = "fnv1a";
+
+/// The fold of a row when splitting into `folds` folds.
+#[inline]
+pub fn fold_of(row: usize, folds: usize) -> usize {
+    row % folds
+}
+
+/// # Target Encoding
+/// A fitted table of smoothed mean labels.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TargetEncoding {
+    /// Mean label over every row, given to unknown categories
+    pub prior: f64,
+    /// Number of rows worth of prior mixed into every category
+    pub prior_weight: f64,
+    /// Fewest rows a category needs to get its own value
+    pub min_count: u64,
+    /// Smoothed mean label by the `stable_hash_of` of the category
+    pub table: HashMap<u64, f64>,
+}
+
+impl TargetEncoding {
+    /// The encoded value of a category
+    #[inline]
+    pub fn encode<T: Hash + ?Sized>(&self, category: &T) -> f64 {
+        match self.table.get(&stable_hash_of(category)) {
+            Some(v) => *v,
+            None => self.prior,
+        }
+    }
+
+    /// Encodes every row with a table fitted on the rows of the other folds, row `j` being in fold `fold_of(j, folds)`.
+    pub fn out_of_fold<T: Hash>(
+        categories: &[T],
+        labels: &[f64],
+        folds: usize,
+        prior_weight: f64,
+        min_count: u64,
+    ) -> Vec<f64> {
+        assert!(folds > 1, "Out of fold encoding needs at least two folds");
+        assert!(
+            categories.len() == labels.len(),
+            "Need one label per category"
+        );
+        let tables: Vec<TargetEncoding> = (0..folds)
+            .map(|fold| {
+                let mut stats = TargetStats::default();
+                for (row, (category, label)) in categories.iter().zip(labels).enumerate() {
+                    if fold_of(row, folds) != fold {
+                        stats.observe(category, *label);
+                    }
+                }
+                stats.finish(prior_weight, min_count)
+            })
+            .collect();
+        categories
+            .iter()
+            .enumerate()
+            .map(|(row, category)| tables[fold_of(row, folds)].encode(category))
+            .collect()
+    }
+}
+
+impl Persist for TargetEncoding {
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(
+            w,
+            "{} {} {} {} {}",
+            KEYS,
+            self.prior,
+            self.prior_weight,
+            self.min_count,
+            self.table.len()
+        )?;
+        let mut entries: Vec<(&u64, &f64)> = self.table.iter().collect();
+        entries.sort_by_key(|(h, _)| **h);
+        for (h, v) in entries {
+            writeln!(w, "{} {}", h, v)?;
+        }
+        Ok(())
+    }
+
+    fn read_from<R: BufRead>(r: &mut R) -> io::Result<Self> {
+        let line = read_line(r)?;
+        let mut parts = line.split(' ');
+        if parts.next() != Some(KEYS) {
+            return Err(invalid(format!("target encoding categories should be keyed by {}", KEYS)));
+        }
+        let prior = parse(parts.next(), "prior")?;
+        let prior_weight = parse(parts.next(), "prior weight")?;
+        let min_count = parse(parts.next(), "minimum count")?;
+        let count: usize = parse(parts.next(), "category count")?;
+        let mut table = HashMap::with_capacity(count);
+        for _ in 0..count {
+            let line = read_line(r)?;
+            let mut parts = line.split(' ');
+            let h: u64 = parse(parts.next(), "category hash")?;
+            let v: f64 = parse(parts.next(), "category value")?;
+            if table.insert(h, v).is_some() {
+                return Err(invalid("repeated category in target encoding"));
+            }
+        }
+        Ok(TargetEncoding {
+            prior,
+            prior_weight,
+            min_count,
+            table,
+        })
+    }
+}
+
+/// # Target Stats
+/// Accumulates label sums and counts per category to fit a `TargetEncoding`.
+#[derive(Debug, Clone, Default)]
+pub struct TargetStats {
+    sum: f64,
+    count: u64,
+    categories: HashMap<u64, (f64, u64)>,
+}
+
+impl TargetStats {
+    /// Adds a labeled row. Rows with a NaN or infinite label are skipped.
+    #[inline]
+    pub fn observe<T: Hash + ?Sized>(&mut self, category: &T, label: f64) {
+        if !label.is_finite() {
+            return;
+        }
+        self.sum += label;
+        self.count += 1;
+        let entry = self.categories.entry(stable_hash_of(category)).or_insert((0.0, 0));
+        entry.0 += label;
+        entry.1 += 1;
+    }
+
+    /// The smoothed table of everything observed so far
+    pub fn finish(&self, prior_weight: f64, min_count: u64) -> TargetEncoding {
+        let prior = if self.count > 0 {
+            self.sum / self.count as f64
+        } else {
+            0.0
+        };
+        let table = self
+            .categories
+            .iter()
+            .filter(|(_, (_, count))| *count >= min_count)
+            .map(|(h, (sum, count))| (*h, (sum + prior * prior_weight) / (*count as f64 + prior_weight)))
+            .collect();
+        TargetEncoding {
+            prior,
+            prior_weight,
+            min_count,
+            table,
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! # Text
+//!
+//! Splits text into tokens and hashes every token, where `make_string_feature!` hashes the
+//! whole string as one. `TextHasher` picks the tokenizer and cleans the tokens up,
+//! `#[struct_feature(text(tokenizer = "words", dim = 512))]` uses one for a field and
+//! `make_text_feature!` builds a standalone featurizer.
+
+use crate::collision::hash_bucket;
+use regex::Regex;
+use std::borrow::Cow;
+use std::collections::HashSet;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// # Tokenizer
+/// How text is split into tokens.
+#[derive(Debug, Clone)]
+pub enum Tokenizer {
+    /// Runs of non whitespace characters
+    Whitespace,
+    /// Words by the unicode word boundary rules, dropping punctuation and spaces
+    Words,
+    /// Every match of the regex
+    Regex(Regex),
+}
+
+impl Tokenizer {
+    /// A regex tokenizer. Panics if the pattern doesn't compile.
+    pub fn regex(pattern: &str) -> Self {
+        match Regex::new(pattern) {
+            Ok(re) => Tokenizer::Regex(re),
+            Err(e) => panic!("Bad tokenizer pattern {:?}: {}", pattern, e),
+        }
+    }
+
+    /// The raw tokens of `text`
+    pub fn split<'s, 't: 's>(&'s self, text: &'t str) -> Box<dyn Iterator<Item = &'t str> + 's> {
+        match self {
+            Tokenizer::Whitespace => Box::new(text.split_whitespace()),
+            Tokenizer::Words => Box::new(text.unicode_words()),
+            Tokenizer::Regex(re) => Box::new(re.find_iter(text).map(|m| m.as_str())),
+        }
+    }
+}
+
+/// # Text Hasher
+/// A tokenizer with the cleanup applied to its tokens, hashing them into the columns of a slice.
+#[derive(Debug, Clone)]
+pub struct TextHasher {
+    /// How the text is split
+    pub tokenizer: Tokenizer,
+    /// Lowercase tokens before anything else
+    pub lowercase: bool,
+    /// Shortest token kept, in characters
+    pub min_len: usize,
+    /// Tokens dropped, compared after lowercasing
+    pub stopwords: HashSet<String>,
+    /// Most tokens kept from one text
+    pub max_tokens: Option<usize>,
+}
+
+impl TextHasher {
+    /// A hasher keeping every token as it is
+    pub fn new(tokenizer: Tokenizer) -> Self {
+        TextHasher {
+            tokenizer,
+            lowercase: false,
+            min_len: 0,
+            stopwords: HashSet::new(),
+            max_tokens: None,
+        }
+    }
+
+    /// Lowercases tokens
+    pub fn lowercase(mut self) -> Self {
+        self.lowercase = true;
+        self
+    }
+
+    /// Drops tokens shorter than `min_len` characters
+    pub fn min_len(mut self, min_len: usize) -> Self {
+        self.min_len = min_len;
+        self
+    }
+
+    /// Drops the given tokens
+    pub fn stopwords<S: Into<String>, I: IntoIterator<Item = S>>(mut self, stopwords: I) -> Self {
+        self.stopwords.extend(stopwords.into_iter().map(Into::into));
+        self
+    }
+
+    /// Keeps at most `max_tokens` tokens of a text
+    pub fn max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// The cleaned up tokens of `text`
+    pub fn tokens<'s, 't: 's>(&'s self, text: &'t str) -> impl Iterator<Item = Cow<'t, str>> + 's {
+        self.tokenizer
+            .split(text)
+            .map(move |t| {
+                if self.lowercase {
+                    Cow::Owned(t.to_lowercase())
+                } else {
+                    Cow::Borrowed(t)
+                }
+            })
+            .filter(move |t| t.chars().count() >= self.min_len && !self.stopwords.contains(t.as_ref()))
+            .take(self.max_tokens.unwrap_or(usize::MAX))
+    }
+
+    /// Adds one to the column of every token
+    #[inline]
+    pub fn fill(&self, text: &str, slice: &mut [f32]) {
+        let dim = slice.len();
+        for token in self.tokens(text) {
+            slice[hash_bucket(token.as_ref(), dim)] += 1.0;
+        }
+    }
+}
+
+/// Builds a featurizer hashing the tokens of strings with a `TextHasher`, built once on first use.
+/// The name should end in the number of columns.
+/// ```
+/// # use ruiso::make_text_feature;
+/// # use ruiso::text::{TextHasher, Tokenizer};
+/// make_text_feature!(Body512, 512, TextHasher::new(Tokenizer::Words).lowercase().min_len(2));
+/// ```
+#[macro_export]
+macro_rules! make_text_feature {
+    ($name:ident, $dim:expr, $hasher:expr) => {
+        #[derive(Debug)]
+        pub struct $name {}
+        impl $name {
+            #[doc(hidden)]
+            pub fn ruiso_text() -> &'static $crate::text::TextHasher {
+                static TEXT: std::sync::OnceLock<$crate::text::TextHasher> = std::sync::OnceLock::new();
+                TEXT.get_or_init(|| $hasher)
+            }
+        }
+        impl<T: AsRef<str>> $crate::Featurizer<T> for $name {
+            #[inline]
+            fn dim() -> usize {
+                $dim
+            }
+            #[inline]
+            fn fill_slice(data: &T, slice: &mut [f32]) {
+                $name::ruiso_text().fill(data.as_ref(), slice);
+            }
+            fn default(_slice: &mut [f32]) {}
+        }
+    };
+}
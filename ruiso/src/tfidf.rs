@@ -0,0 +1,317 @@
+//! # TF-IDF
+//!
+//! Weighs the tokens of a text by how rare they are over the training documents. The columns are
+//! either hashed buckets or a vocabulary of the tokens found in the most documents, and the document
+//! frequency of every column is fitted, giving `idf = ln((1 + docs) / (1 + df)) + 1`.
+//! A text is written as `tf * idf` per column, with `tf` the token count or `1 + ln(count)` when
+//! sublinear, and the block can be L2 normalized. Hashed columns use `fitted::stable_bucket`, so
+//! saved frequencies keep pointing at the same tokens.
+//!
+//! `#[struct_feature(tfidf(dim = 1024, tokenizer = "words", sublinear, l2))]` fits it like the other
+//! fitted fields, and `make_tfidf_feature!` builds a standalone featurizer. Tokens come from a `TextHasher`.
+
+use crate::fitted::{invalid, parse, read_line, stable_bucket, Persist};
+use crate::vocab::{TokenCounter, Vocabulary};
+use std::collections::HashSet;
+use std::io::{self, BufRead, Write};
+
+/// # TF-IDF Columns
+/// How tokens are mapped to columns.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TfIdfColumns {
+    /// Hashed into this many buckets with `stable_bucket`
+    Hashed(usize),
+    /// One column per token of the vocabulary, dropping the others
+    Vocab(Vocabulary),
+}
+
+/// # TF-IDF
+/// Fitted inverse document frequencies of every column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TfIdf {
+    /// How tokens are mapped to columns
+    pub columns: TfIdfColumns,
+    /// Inverse document frequency of every column
+    pub idf: Vec<f64>,
+    /// Number of documents fitted on
+    pub docs: u64,
+    /// Use `1 + ln(count)` as the term frequency
+    pub sublinear: bool,
+    /// Scale the block to unit L2 norm
+    pub l2: bool,
+}
+
+impl TfIdf {
+    /// Number of columns
+    pub fn dim(&self) -> usize {
+        self.idf.len()
+    }
+
+    /// Column of a token, if it has one
+    #[inline]
+    pub fn column(&self, token: &str) -> Option<usize> {
+        match &self.columns {
+            TfIdfColumns::Hashed(dim) => Some(stable_bucket(token, *dim)),
+            TfIdfColumns::Vocab(vocab) => {
+                let j = vocab.index(token);
+                if j < vocab.tokens.len() {
+                    Some(j)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Writes the weights of the tokens of one document into the slice
+    pub fn fill<S: AsRef<str>>(&self, tokens: &[S], slice: &mut [f32]) {
+        let mut counts = vec![0.0f64; self.dim()];
+        for token in tokens {
+            if let Some(j) = self.column(token.as_ref()) {
+                counts[j] += 1.0;
+            }
+        }
+        let mut norm = 0.0;
+        for (j, count) in counts.iter_mut().enumerate() {
+            if *count > 0.0 {
+                let tf = if self.sublinear { 1.0 + count.ln() } else { *count };
+                *count = tf * self.idf[j];
+                norm += *count * *count;
+            }
+        }
+        let scale = if self.l2 && norm > 0.0 { 1.0 / norm.sqrt() } else { 1.0 };
+        for (s, w) in slice.iter_mut().zip(counts.iter()) {
+            *s = (*w * scale) as f32;
+        }
+    }
+}
+
+impl Persist for TfIdf {
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let columns = match &self.columns {
+            TfIdfColumns::Hashed(_) => "hashed_fnv1a",
+            TfIdfColumns::Vocab(_) => "vocab",
+        };
+        writeln!(
+            w,
+            "{} {} {} {} {}",
+            columns,
+            self.docs,
+            if self.sublinear { "sublinear" } else { "linear" },
+            if self.l2 { "l2" } else { "raw" },
+            self.idf.len()
+        )?;
+        let idf: Vec<String> = self.idf.iter().map(|x| x.to_string()).collect();
+        writeln!(w, "{}", idf.join(" "))?;
+        if let TfIdfColumns::Vocab(vocab) = &self.columns {
+            vocab.write_to(w)?;
+        }
+        Ok(())
+    }
+
+    fn read_from<R: BufRead>(r: &mut R) -> io::Result<Self> {
+        let line = read_line(r)?;
+        let mut parts = line.split(' ');
+        let columns = parts.next().map(str::to_string);
+        let docs = parse(parts.next(), "document count")?;
+        let sublinear = match parts.next() {
+            Some("sublinear") => true,
+            Some("linear") => false,
+            other => return Err(invalid(format!("unknown term frequency {:?}", other))),
+        };
+        let l2 = match parts.next() {
+            Some("l2") => true,
+            Some("raw") => false,
+            other => return Err(invalid(format!("unknown normalization {:?}", other))),
+        };
+        let dim: usize = parse(parts.next(), "column count")?;
+        let line = read_line(r)?;
+        let idf = line
+            .split(' ')
+            .filter(|x| !x.is_empty())
+            .map(|x| parse(Some(x), "idf"))
+            .collect::<io::Result<Vec<f64>>>()?;
+        if idf.len() != dim {
+            return Err(invalid("wrong number of idf values"));
+        }
+        let columns = match columns.as_deref() {
+            Some("hashed_fnv1a") => TfIdfColumns::Hashed(dim),
+            Some("vocab") => {
+                let vocab = Vocabulary::read_from(r)?;
+                if vocab.capacity != dim {
+                    return Err(invalid("tf-idf vocabulary doesn't match its idf values"));
+                }
+                TfIdfColumns::Vocab(vocab)
+            }
+            other => return Err(invalid(format!("unknown tf-idf columns {:?}", other))),
+        };
+        Ok(TfIdf {
+            columns,
+            idf,
+            docs,
+            sublinear,
+            l2,
+        })
+    }
+}
+
+/// # Document Frequencies
+/// Counts the documents every column appears in to fit a `TfIdf`.
+#[derive(Debug, Clone)]
+pub struct DocFreqs {
+    docs: u64,
+    hashed: Vec<u64>,
+    tokens: TokenCounter,
+    vocab: Option<(usize, usize)>,
+}
+
+impl DocFreqs {
+    /// Counts for `dim` hashed buckets
+    pub fn hashed(dim: usize) -> Self {
+        assert!(dim > 0, "The dimension of a hashing featurizer can't be 0");
+        DocFreqs {
+            docs: 0,
+            hashed: vec![0; dim],
+            tokens: TokenCounter::default(),
+            vocab: None,
+        }
+    }
+
+    /// Counts for a vocabulary of the `top_k` tokens found in the most documents, and in at least `min_df`
+    pub fn vocab(top_k: usize, min_df: usize) -> Self {
+        DocFreqs {
+            docs: 0,
+            hashed: Vec::new(),
+            tokens: TokenCounter::default(),
+            vocab: Some((top_k, min_df)),
+        }
+    }
+
+    /// Adds the tokens of one document
+    pub fn observe<S: AsRef<str>>(&mut self, tokens: &[S]) {
+        self.docs += 1;
+        match self.vocab {
+            Some(_) => {
+                let unique: HashSet<&str> = tokens.iter().map(|t| t.as_ref()).collect();
+                for token in unique {
+                    self.tokens.observe(token);
+                }
+            }
+            None => {
+                let dim = self.hashed.len();
+                let unique: HashSet<usize> = tokens.iter().map(|t| stable_bucket(t.as_ref(), dim)).collect();
+                for j in unique {
+                    self.hashed[j] += 1;
+                }
+            }
+        }
+    }
+
+    /// The fitted weights of everything observed so far
+    pub fn finish(&self, sublinear: bool, l2: bool) -> TfIdf {
+        let idf_of = |df: u64| ((1 + self.docs) as f64 / (1 + df) as f64).ln() + 1.0;
+        let (columns, idf) = match self.vocab {
+            Some((top_k, min_df)) => {
+                let vocab = self.tokens.finish(top_k, min_df);
+                let mut idf: Vec<f64> = vocab
+                    .tokens
+                    .iter()
+                    .map(|t| idf_of(self.tokens.count(t) as u64))
+                    .collect();
+                idf.resize(top_k, idf_of(0));
+                (TfIdfColumns::Vocab(vocab), idf)
+            }
+            None => (
+                TfIdfColumns::Hashed(self.hashed.len()),
+                self.hashed.iter().map(|df| idf_of(*df)).collect(),
+            ),
+        };
+        TfIdf {
+            columns,
+            idf,
+            docs: self.docs,
+            sublinear,
+            l2,
+        }
+    }
+}
+
+/// Builds a TF-IDF featurizer for strings, tokenizing with a `TextHasher` built once on first use.
+/// The name should end in the number of columns. Its weights are fitted with `fit` and installed with
+/// `install` or `load`.
+/// ```
+/// # use ruiso::make_tfidf_feature;
+/// # use ruiso::text::{TextHasher, Tokenizer};
+/// # use ruiso::tfidf::DocFreqs;
+/// make_tfidf_feature!(Body1024, 1024, TextHasher::new(Tokenizer::Words).lowercase());
+/// # fn main() -> std::io::Result<()> {
+/// # let docs = vec!["Run the script", "run it again"];
+/// Body1024::install(Body1024::fit(docs.iter(), DocFreqs::hashed(1024), true, true))?;
+/// # Ok(())
+/// # }
+/// ```
+#[macro_export]
+macro_rules! make_tfidf_feature {
+    ($name:ident, $dim:expr, $hasher:expr) => {
+        #[derive(Debug)]
+        pub struct $name {}
+        impl $name {
+            #[doc(hidden)]
+            pub fn ruiso_text() -> &'static $crate::text::TextHasher {
+                static TEXT: std::sync::OnceLock<$crate::text::TextHasher> = std::sync::OnceLock::new();
+                TEXT.get_or_init(|| $hasher)
+            }
+            #[doc(hidden)]
+            pub fn ruiso_tfidf() -> &'static $crate::fitted::FittedSlot<$crate::tfidf::TfIdf> {
+                static SLOT: $crate::fitted::FittedSlot<$crate::tfidf::TfIdf> = $crate::fitted::FittedSlot::new();
+                &SLOT
+            }
+            /// The tokens of a text
+            pub fn tokens(text: &str) -> Vec<String> {
+                Self::ruiso_text().tokens(text).map(|t| t.into_owned()).collect()
+            }
+            /// Fits the weights over the documents
+            pub fn fit<S: AsRef<str>, I: IntoIterator<Item = S>>(
+                docs: I,
+                mut freqs: $crate::tfidf::DocFreqs,
+                sublinear: bool,
+                l2: bool,
+            ) -> $crate::tfidf::TfIdf {
+                for doc in docs {
+                    freqs.observe(&Self::tokens(doc.as_ref()));
+                }
+                freqs.finish(sublinear, l2)
+            }
+            /// Installs the weights for every following featurization.
+            pub fn install(tfidf: $crate::tfidf::TfIdf) -> std::io::Result<()> {
+                if tfidf.dim() != $dim {
+                    return Err(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("{} has {} columns, not {}", stringify!($name), $dim, tfidf.dim()),
+                    ));
+                }
+                Self::ruiso_tfidf().install(tfidf);
+                Ok(())
+            }
+            /// Loads weights saved with `Persist::save` and installs them.
+            pub fn load<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<()> {
+                Self::install(<$crate::tfidf::TfIdf as $crate::fitted::Persist>::load(path)?)
+            }
+        }
+        impl<T: AsRef<str>> $crate::Featurizer<T> for $name {
+            #[inline]
+            fn dim() -> usize {
+                $dim
+            }
+            #[inline]
+            fn fill_slice(data: &T, slice: &mut [f32]) {
+                let tfidf = match $name::ruiso_tfidf().get() {
+                    Some(tfidf) => tfidf,
+                    None => panic!("{} has no weights, call {}::install first", stringify!($name), stringify!($name)),
+                };
+                tfidf.fill(&$name::tokens(data.as_ref()), slice);
+            }
+            fn default(_slice: &mut [f32]) {}
+        }
+    };
+}
@@ -0,0 +1,206 @@
+//! # Timestamps
+//!
+//! Expands a timestamp into calendar components. `StructFeature` does this for `SystemTime`
+//! fields, and for `chrono::DateTime` and `chrono::NaiveDateTime` with the `chrono` feature.
+//! The components are picked with `#[struct_feature(time(hour, weekday, age, utc_offset = -18000))]`,
+//! and `make_time_feature!` builds a standalone featurizer.
+
+use crate::numeric::split_int;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Components written when a timestamp field doesn't pick its own.
+pub const DEFAULT_COMPONENTS: &[TimeComponent] = &[
+    TimeComponent::Hour,
+    TimeComponent::Weekday,
+    TimeComponent::Month,
+    TimeComponent::DayOfMonth,
+    TimeComponent::IsWeekend,
+];
+
+/// # Time Component
+/// One column of an expanded timestamp.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeComponent {
+    /// Hour of the day, 0 to 23
+    Hour,
+    /// Day of the week, 0 for Monday to 6 for Sunday
+    Weekday,
+    /// Month, 1 to 12
+    Month,
+    /// Day of the month, 1 to 31
+    DayOfMonth,
+    /// 1 on Saturdays and Sundays
+    IsWeekend,
+    /// Whole seconds since the unix epoch, ignoring the offset, as the three exact columns of
+    /// `numeric::split_int`. A single `f32` would round current timestamps to about two minutes.
+    EpochSeconds,
+    /// Seconds from the timestamp to the reference time, positive for timestamps in the past
+    Age,
+}
+
+impl TimeComponent {
+    /// Number of columns the component takes
+    pub const fn width(self) -> usize {
+        match self {
+            TimeComponent::EpochSeconds => crate::numeric::SPLIT_PARTS,
+            _ => 1,
+        }
+    }
+}
+
+/// Number of columns written by `fill_time` for these components
+pub const fn time_width(components: &[TimeComponent]) -> usize {
+    let mut width = 0;
+    let mut j = 0;
+    while j < components.len() {
+        width += components[j].width();
+        j += 1;
+    }
+    width
+}
+
+/// Whether the components need a reference time, that is whether one of them is `Age`
+pub const fn needs_reference(components: &[TimeComponent]) -> bool {
+    let mut j = 0;
+    while j < components.len() {
+        if let TimeComponent::Age = components[j] {
+            return true;
+        }
+        j += 1;
+    }
+    false
+}
+
+/// # Timestamp
+/// Anything that can be placed on the unix time line.
+pub trait Timestamp {
+    /// Seconds since 1970-01-01 00:00 UTC, negative before it
+    fn epoch_seconds(&self) -> f64;
+}
+
+impl Timestamp for SystemTime {
+    fn epoch_seconds(&self) -> f64 {
+        match self.duration_since(UNIX_EPOCH) {
+            Ok(d) => d.as_secs_f64(),
+            Err(e) => -e.duration().as_secs_f64(),
+        }
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> Timestamp for chrono::DateTime<Tz> {
+    fn epoch_seconds(&self) -> f64 {
+        self.timestamp() as f64 + self.timestamp_subsec_nanos() as f64 * 1e-9
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl Timestamp for chrono::NaiveDateTime {
+    /// Naive times are read as UTC
+    fn epoch_seconds(&self) -> f64 {
+        self.and_utc().epoch_seconds()
+    }
+}
+
+/// (year, month, day) of a count of days since the epoch, from Howard Hinnant's `civil_from_days`.
+pub fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Fills the columns of every component, `time_width` in all, for a timestamp given in epoch seconds.
+/// Calendar components are taken at `utc_offset` seconds from UTC.
+/// `Age` is measured to `reference`, a fixed time so a row featurizes the same way at training and
+/// serving time, and panics without one.
+pub fn fill_time(
+    epoch_seconds: f64,
+    components: &[TimeComponent],
+    utc_offset: i64,
+    reference: Option<f64>,
+    slice: &mut [f32],
+) {
+    let local = epoch_seconds.floor() as i64 + utc_offset;
+    let days = local.div_euclid(86_400);
+    let second_of_day = local.rem_euclid(86_400);
+    let weekday = (days + 3).rem_euclid(7);
+    let mut j = 0;
+    for component in components {
+        if let TimeComponent::EpochSeconds = component {
+            slice[j..j + component.width()].copy_from_slice(&split_int(epoch_seconds.floor() as i128));
+            j += component.width();
+            continue;
+        }
+        slice[j] = match component {
+            TimeComponent::Hour => (second_of_day / 3600) as f32,
+            TimeComponent::Weekday => weekday as f32,
+            TimeComponent::Month => civil_from_days(days).1 as f32,
+            TimeComponent::DayOfMonth => civil_from_days(days).2 as f32,
+            TimeComponent::IsWeekend => {
+                if weekday >= 5 {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+            TimeComponent::EpochSeconds => unreachable!(),
+            TimeComponent::Age => match reference {
+                Some(reference) => (reference - epoch_seconds) as f32,
+                None => panic!("The age of a timestamp needs a reference time"),
+            },
+        };
+        j += 1;
+    }
+}
+
+/// Builds a timestamp featurizer with the desired name and components, for every `Timestamp`.
+/// The name should end in the number of columns. An offset from UTC in seconds can follow, then the
+/// reference time in epoch seconds, which `Age` can't do without.
+/// ```
+/// # use ruiso::make_time_feature;
+/// make_time_feature!(EventTime3, [Hour, Weekday, IsWeekend]);
+/// make_time_feature!(LocalTime1, [Hour], -5 * 3600);
+/// make_time_feature!(Seen4, [Age, EpochSeconds], 0, 1_600_000_000);
+/// ```
+#[macro_export]
+macro_rules! make_time_feature {
+    ($name:ident, [$($component:ident),*]) => {
+        $crate::make_time_feature!($name, [$($component),*], 0);
+    };
+    ($name:ident, [$($component:ident),*], $utc_offset:expr) => {
+        $crate::make_time_feature!(@impl $name, [$($component),*], $utc_offset, None);
+    };
+    ($name:ident, [$($component:ident),*], $utc_offset:expr, $reference:expr) => {
+        $crate::make_time_feature!(@impl $name, [$($component),*], $utc_offset, Some($reference as f64));
+    };
+    (@impl $name:ident, [$($component:ident),*], $utc_offset:expr, $reference:expr) => {
+        #[derive(Debug)]
+        pub struct $name {}
+        impl $name {
+            const COMPONENTS: &'static [$crate::time::TimeComponent] = &[$($crate::time::TimeComponent::$component),*];
+            const REFERENCE: Option<f64> = $reference;
+        }
+        const _: () = assert!(
+            !$crate::time::needs_reference($name::COMPONENTS) || $name::REFERENCE.is_some(),
+            "Age needs a reference time, give it after the utc offset"
+        );
+        impl<T: $crate::time::Timestamp> $crate::Featurizer<T> for $name {
+            #[inline]
+            fn dim() -> usize {
+                $crate::time::time_width($name::COMPONENTS)
+            }
+            #[inline]
+            fn fill_slice(data: &T, slice: &mut [f32]) {
+                $crate::time::fill_time(data.epoch_seconds(), $name::COMPONENTS, $utc_offset, $name::REFERENCE, slice);
+            }
+            fn default(_slice: &mut [f32]) {}
+        }
+    };
+}
@@ -0,0 +1,51 @@
+//! # Numeric transforms
+//!
+//! Stateless transforms for numeric fields. `StructFeature` applies these for
+//! `#[struct_feature(transform = "clip(0, 1e6) | log1p")]`, `scale = 0.001` and `offset = 3.0`,
+//! in the order they are written. They are plain functions so custom featurizers can use them too.
+
+/// `ln(1 + x)`
+#[inline]
+pub fn log1p(x: f64) -> f64 {
+    x.ln_1p()
+}
+
+/// `ln(1 + |x|)` carrying the sign of `x`, for heavy tailed values that can be negative
+#[inline]
+pub fn signed_log(x: f64) -> f64 {
+    if x < 0.0 {
+        -(-x).ln_1p()
+    } else {
+        x.ln_1p()
+    }
+}
+
+/// Square root, NaN for negative values
+#[inline]
+pub fn sqrt(x: f64) -> f64 {
+    x.sqrt()
+}
+
+/// Clamps `x` to `[min, max]`
+#[inline]
+pub fn clip(x: f64, min: f64, max: f64) -> f64 {
+    x.max(min).min(max)
+}
+
+/// Multiplies `x` by `factor`
+#[inline]
+pub fn scale(x: f64, factor: f64) -> f64 {
+    x * factor
+}
+
+/// Adds `by` to `x`
+#[inline]
+pub fn offset(x: f64, by: f64) -> f64 {
+    x + by
+}
+
+/// Maps `[min, max]` onto `[0, 1]` with fixed bounds. Values outside the bounds are not clipped.
+#[inline]
+pub fn min_max(x: f64, min: f64, max: f64) -> f64 {
+    (x - min) / (max - min)
+}
@@ -0,0 +1,193 @@
+//! # Vocabularies
+//!
+//! One hot encoding of strings against a known list of tokens, with a last column for
+//! everything out of the vocabulary. `#[struct_feature(vocab = ["tcp", "udp", "icmp"])]`
+//! uses a fixed list, `#[struct_feature(vocab(top_k = 100, min_count = 5))]` keeps the most
+//! frequent tokens of the training data. `make_vocab_feature!` builds a standalone featurizer.
+
+use crate::fitted::{escape, invalid, parse, read_line, unescape, Persist};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+/// # Vocabulary
+/// Tokens with a column each, plus one out of vocabulary column.
+/// The width is fixed by `capacity`, so a fitted vocabulary with fewer tokens keeps the layout.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Vocabulary {
+    /// Tokens in column order
+    pub tokens: Vec<String>,
+    /// Columns reserved for tokens, at least `tokens.len()`
+    pub capacity: usize,
+    index: HashMap<String, usize>,
+}
+
+impl Vocabulary {
+    /// A vocabulary of `tokens`, with as many columns as tokens.
+    /// Repeated tokens keep their first column.
+    pub fn new<S: Into<String>, I: IntoIterator<Item = S>>(tokens: I) -> Self {
+        let tokens: Vec<String> = tokens.into_iter().map(Into::into).collect();
+        let capacity = tokens.len();
+        Vocabulary::with_capacity(tokens, capacity)
+    }
+
+    /// A vocabulary of `tokens` reserving `capacity` token columns. Panics if the tokens don't fit.
+    pub fn with_capacity(tokens: Vec<String>, capacity: usize) -> Self {
+        assert!(
+            tokens.len() <= capacity,
+            "{} tokens don't fit in a vocabulary of {}",
+            tokens.len(),
+            capacity
+        );
+        let mut index = HashMap::with_capacity(tokens.len());
+        for (j, token) in tokens.iter().enumerate() {
+            index.entry(token.clone()).or_insert(j);
+        }
+        Vocabulary {
+            tokens,
+            capacity,
+            index,
+        }
+    }
+
+    /// Number of columns, the token columns and the out of vocabulary column
+    pub fn dim(&self) -> usize {
+        self.capacity + 1
+    }
+
+    /// Column of the out of vocabulary bucket
+    pub fn oov(&self) -> usize {
+        self.capacity
+    }
+
+    /// Column of `token`, the out of vocabulary column if it isn't known
+    #[inline]
+    pub fn index(&self, token: &str) -> usize {
+        match self.index.get(token) {
+            Some(j) => *j,
+            None => self.capacity,
+        }
+    }
+}
+
+impl Persist for Vocabulary {
+    fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "{} {}", self.tokens.len(), self.capacity)?;
+        for token in self.tokens.iter() {
+            writeln!(w, "{}", escape(token))?;
+        }
+        Ok(())
+    }
+
+    fn read_from<R: BufRead>(r: &mut R) -> io::Result<Self> {
+        let line = read_line(r)?;
+        let mut parts = line.split(' ');
+        let count: usize = parse(parts.next(), "token count")?;
+        let capacity: usize = parse(parts.next(), "vocabulary capacity")?;
+        if count > capacity {
+            return Err(invalid("more tokens than the vocabulary capacity"));
+        }
+        let tokens = (0..count)
+            .map(|_| unescape(&read_line(r)?))
+            .collect::<io::Result<Vec<String>>>()?;
+        Ok(Vocabulary::with_capacity(tokens, capacity))
+    }
+}
+
+/// # Token Counter
+/// Counts tokens to fit a `Vocabulary`.
+#[derive(Debug, Clone, Default)]
+pub struct TokenCounter {
+    counts: HashMap<String, usize>,
+}
+
+impl TokenCounter {
+    /// Adds one occurrence of `token`
+    #[inline]
+    pub fn observe(&mut self, token: &str) {
+        match self.counts.get_mut(token) {
+            Some(count) => *count += 1,
+            None => {
+                self.counts.insert(token.to_string(), 1);
+            }
+        }
+    }
+
+    /// Number of times `token` was seen
+    pub fn count(&self, token: &str) -> usize {
+        self.counts.get(token).copied().unwrap_or(0)
+    }
+
+    /// The `top_k` most frequent tokens seen at least `min_count` times, most frequent first.
+    /// Ties are broken alphabetically so fitting is deterministic.
+    pub fn finish(&self, top_k: usize, min_count: usize) -> Vocabulary {
+        let mut ranked: Vec<(&String, usize)> = self
+            .counts
+            .iter()
+            .filter(|(_, count)| **count >= min_count)
+            .map(|(token, count)| (token, *count))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+        let tokens = ranked
+            .into_iter()
+            .take(top_k)
+            .map(|(token, _)| token.clone())
+            .collect();
+        Vocabulary::with_capacity(tokens, top_k)
+    }
+}
+
+/// Whether a token is listed twice. Used by `make_vocab_feature!` to reject such lists when compiling.
+pub const fn has_duplicates(tokens: &[&str]) -> bool {
+    let mut a = 0;
+    while a < tokens.len() {
+        let mut b = a + 1;
+        while b < tokens.len() {
+            let (x, y) = (tokens[a].as_bytes(), tokens[b].as_bytes());
+            if x.len() == y.len() {
+                let mut j = 0;
+                while j < x.len() && x[j] == y[j] {
+                    j += 1;
+                }
+                if j == x.len() {
+                    return true;
+                }
+            }
+            b += 1;
+        }
+        a += 1;
+    }
+    false
+}
+
+/// Builds a featurizer one hot encoding strings against a fixed vocabulary.
+/// The name should end in the number of tokens plus one, for the out of vocabulary column.
+/// ```
+/// # use ruiso::{make_vocab_feature, Featurizer};
+/// make_vocab_feature!(Protocol4, ["tcp", "udp", "icmp"]);
+/// # assert!(Protocol4::featurize(&"udp") == vec![0.0, 1.0, 0.0, 0.0]);
+/// ```
+#[macro_export]
+macro_rules! make_vocab_feature {
+    ($name:ident, [$($token:expr),* $(,)?]) => {
+        #[derive(Debug)]
+        pub struct $name {}
+        const _: () = assert!(
+            !$crate::vocab::has_duplicates(&[$($token),*]),
+            concat!("A token of ", stringify!($name), " is listed twice")
+        );
+        impl<T: AsRef<str>> $crate::Featurizer<T> for $name {
+            #[inline]
+            fn dim() -> usize {
+                [$($token),*].len() + 1
+            }
+            #[inline]
+            fn fill_slice(data: &T, slice: &mut [f32]) {
+                let tokens: &[&str] = &[$($token),*];
+                let data = data.as_ref();
+                let j = tokens.iter().position(|t| *t == data).unwrap_or(tokens.len());
+                slice[j] = 1.0;
+            }
+            fn default(_slice: &mut [f32]) {}
+        }
+    };
+}
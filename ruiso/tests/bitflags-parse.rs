@@ -0,0 +1,91 @@
+#![cfg(feature = "bitflags")]
+
+use bitflags::bitflags;
+use ruiso::flags::flag_names;
+use ruiso::*;
+
+bitflags! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub struct TcpFlags: u8 {
+        const FIN = 0b0000_0001;
+        const SYN = 0b0000_0010;
+        const RST = 0b0000_0100;
+        const ACK = 0b0001_0000;
+    }
+}
+
+make_flags_feature!(TcpFlagsFeaturizer4, TcpFlags);
+
+#[derive(StructFeature)]
+pub struct FlagsTestStruct {
+    #[struct_feature(featurizer = "TcpFlagsFeaturizer4")]
+    flags: Option<TcpFlags>,
+}
+
+#[derive(StructFeature)]
+pub struct DirectFlagsTestStruct {
+    foo: u32,
+    #[struct_feature(flags = 4)]
+    flags: TcpFlags,
+    #[struct_feature(bits = 2)]
+    bar: u8,
+    #[struct_feature(flags = 4)]
+    more: Option<TcpFlags>,
+    kal: f32,
+}
+
+#[derive(StructFeature)]
+pub struct NestedFlagsTestStruct {
+    #[struct_feature(featurizer = "DirectFlagsTestStructFeaturizer12")]
+    inner: DirectFlagsTestStruct,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_in_order() {
+        assert!(flag_names::<TcpFlags>() == vec!["FIN", "SYN", "RST", "ACK"]);
+    }
+
+    #[test]
+    fn fill_flags_correct() {
+        let st = FlagsTestStruct {
+            flags: Some(TcpFlags::SYN | TcpFlags::ACK),
+        };
+        assert!(st.featurize() == vec![0.0, 1.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn direct_flags_correct() {
+        assert!(DirectFlagsTestStruct::dim() == 1 + 4 + 2 + 4 + 1);
+        assert!(<DirectFlagsTestStructFeaturizer12 as Featurizer<DirectFlagsTestStruct>>::dim() == 12);
+        let st = DirectFlagsTestStruct {
+            foo: 7,
+            flags: TcpFlags::FIN | TcpFlags::ACK,
+            bar: 0b10,
+            more: None,
+            kal: 2.5,
+        };
+        let data = st.featurize();
+        assert!(data == vec![7.0, 1.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 2.5]);
+        assert!(DirectFlagsTestStructFeaturizer12::featurize(&st) == data);
+        let st = DirectFlagsTestStruct {
+            more: Some(TcpFlags::RST),
+            ..st
+        };
+        assert!(st.featurize()[7..11] == [0.0, 0.0, 1.0, 0.0]);
+        let nested = NestedFlagsTestStruct { inner: st };
+        assert!(nested.featurize() == nested.inner.featurize());
+    }
+
+    #[test]
+    fn direct_flags_named() {
+        let names = DirectFlagsTestStruct::feature_names();
+        assert!(names.len() == DirectFlagsTestStruct::dim());
+        assert!(names[..5] == ["foo", "flags:FIN", "flags:SYN", "flags:RST", "flags:ACK"]);
+        assert!(names[5..7] == ["bar[0]", "bar[1]"]);
+        assert!(names[7] == "more:FIN" && names[11] == "kal");
+    }
+}
@@ -0,0 +1,76 @@
+use ruiso::bucket::{bucket_index, QuantileSketch};
+use ruiso::*;
+
+#[derive(StructFeature)]
+pub struct BucketTestStruct {
+    foo: u32,
+    #[struct_feature(buckets = [0, 10, 100, 1000])]
+    port: u32,
+    #[struct_feature(buckets = [-1.5, 1.5])]
+    size: Option<f32>,
+}
+
+#[derive(StructFeature)]
+pub struct QuantileTestStruct {
+    #[struct_feature(quantiles = 4)]
+    size: f64,
+    bar: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_boundaries() {
+        let bounds = [0.0, 10.0, 100.0];
+        assert!(bucket_index(-1.0, &bounds) == Some(0));
+        assert!(bucket_index(0.0, &bounds) == Some(1));
+        assert!(bucket_index(99.0, &bounds) == Some(2));
+        assert!(bucket_index(1e9, &bounds) == Some(3));
+        assert!(bucket_index(f64::NAN, &bounds).is_none());
+    }
+
+    #[test]
+    fn fill_buckets_correct() {
+        assert!(BucketTestStruct::dim() == 1 + 5 + 3);
+        let st = BucketTestStruct {
+            foo: 2,
+            port: 443,
+            size: Some(-2.0),
+        };
+        let data = st.featurize();
+        assert!(data == vec![2.0, 0.0, 0.0, 0.0, 1.0, 0.0, 1.0, 0.0, 0.0]);
+        let st = BucketTestStruct {
+            foo: 2,
+            port: 5,
+            size: None,
+        };
+        let data = st.featurize();
+        assert!(data == vec![2.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn quantiles_fitted() {
+        let mut sketch = QuantileSketch::default();
+        for x in 0..100 {
+            sketch.observe(x as f64);
+        }
+        assert!(sketch.finish(4).boundaries == vec![25.0, 50.0, 75.0]);
+    }
+
+    #[test]
+    fn fill_quantiles_correct() {
+        let data: Vec<QuantileTestStruct> = (0..8)
+            .map(|x| QuantileTestStruct {
+                size: x as f64,
+                bar: 1,
+            })
+            .collect();
+        let fitted = QuantileTestStruct::fit(&data);
+        assert!(fitted.quantiles(0).boundaries == vec![2.0, 4.0, 6.0]);
+        QuantileTestStruct::install_fitted(fitted).unwrap();
+        assert!(QuantileTestStruct::dim() == 5);
+        assert!(data[5].featurize() == vec![0.0, 0.0, 1.0, 0.0, 1.0]);
+    }
+}
@@ -0,0 +1,80 @@
+use ruiso::bytes::ByteNgramHasher;
+use ruiso::collision::hash_bucket;
+use ruiso::*;
+use std::io::Read;
+
+make_byte_ngram_feature!(Payload64, 64, ByteNgramHasher::new(2).stride(2));
+
+#[derive(StructFeature)]
+pub struct ByteNgramTestStruct {
+    #[struct_feature(byte_ngrams(n = 3, dim = 16))]
+    payload: Vec<u8>,
+    #[struct_feature(byte_ngrams(n = 1, max_bytes = 2, dim = 8, binary))]
+    header: Option<Vec<u8>>,
+}
+
+// Hands out one byte per read, to cut n-grams across reads.
+struct Trickle<'a>(&'a [u8]);
+
+impl Read for Trickle<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.0.is_empty() || buf.is_empty() {
+            return Ok(0);
+        }
+        buf[0] = self.0[0];
+        self.0 = &self.0[1..];
+        Ok(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ngrams_counted() {
+        let data = Payload64::featurize(&vec![1u8, 2, 1, 2, 3]);
+        assert!(data.iter().sum::<f32>() == 2.0);
+        assert!(data[hash_bucket(&[1u8, 2][..], 64)] == 2.0);
+    }
+
+    #[test]
+    fn reader_matches_slice() {
+        let data: Vec<u8> = (0..20000u32).map(|x| (x * 7 % 251) as u8).collect();
+        for hasher in [
+            ByteNgramHasher::new(4),
+            ByteNgramHasher::new(3).stride(5).max_bytes(10001),
+            ByteNgramHasher::new(2).stride(9000).binary(),
+        ] {
+            let mut expected = vec![0.0; 128];
+            hasher.fill(&data, &mut expected);
+            let mut streamed = vec![0.0; 128];
+            hasher.fill_reader(&data[..], &mut streamed).unwrap();
+            assert!(streamed == expected);
+            let mut trickled = vec![0.0; 128];
+            hasher.fill_reader(Trickle(&data[..100]), &mut trickled).unwrap();
+            let mut short = vec![0.0; 128];
+            hasher.fill(&data[..100], &mut short);
+            assert!(trickled == short);
+        }
+        assert!(Payload64::featurize_reader(&[1u8, 2, 1, 2, 3][..]).unwrap() == Payload64::featurize(&vec![1u8, 2, 1, 2, 3]));
+    }
+
+    #[test]
+    fn fill_byte_ngrams_correct() {
+        assert!(ByteNgramTestStruct::dim() == 24);
+        let st = ByteNgramTestStruct {
+            payload: vec![0; 5],
+            header: Some(vec![7, 7, 9]),
+        };
+        let data = st.featurize();
+        assert!(data[hash_bucket(&[0u8, 0, 0][..], 16)] == 3.0);
+        assert!(data[16 + hash_bucket(&[7u8][..], 8)] == 1.0);
+        assert!(data[16..].iter().sum::<f32>() == 1.0);
+        let none = ByteNgramTestStruct {
+            payload: vec![],
+            header: None,
+        };
+        assert!(none.featurize() == vec![0.0; 24]);
+    }
+}
@@ -0,0 +1,60 @@
+use ruiso::bytes::{fill_byte_entropy, ByteEntropyHistogram, ByteHistogram};
+use ruiso::*;
+
+#[derive(StructFeature)]
+pub struct BytesTestStruct<'a> {
+    #[struct_feature(bytes)]
+    payload: Vec<u8>,
+    #[struct_feature(bytes(length, histogram))]
+    header: &'a [u8],
+    #[struct_feature(bytes(entropy, window = 4, step = 2))]
+    body: Option<Vec<u8>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn histogram_normalized() {
+        let data = ByteHistogram::featurize(&vec![0u8, 0, 255, 7]);
+        assert!(data[0] == 0.5 && data[7] == 0.25 && data[255] == 0.25);
+        assert!(data.iter().sum::<f32>() == 1.0);
+        assert!(ByteHistogram::featurize(&Vec::<u8>::new()) == vec![0.0; 256]);
+    }
+
+    #[test]
+    fn entropy_rows() {
+        // One nibble only: entropy 0, everything in row 0.
+        let data = ByteEntropyHistogram::featurize(&vec![0x11u8; 100]);
+        assert!(data[1] == 1.0);
+        // Four nibbles evenly: 2 bits, row 8.
+        let mut slice = [0.0; 256];
+        fill_byte_entropy(&[0x00, 0x10, 0x20, 0x30], 4, 4, &mut slice);
+        assert!(slice[8 * 16..8 * 16 + 4] == [0.25; 4]);
+        // Two windows of four bytes out of six.
+        let mut slice = [0.0; 256];
+        fill_byte_entropy(&[0, 0, 0, 0, 0, 0x10], 4, 2, &mut slice);
+        assert!(slice[0] == 0.5 + 0.5 * 0.0);
+        assert!(slice[3 * 16] == 3.0 / 8.0 && slice[3 * 16 + 1] == 1.0 / 8.0);
+    }
+
+    #[test]
+    fn fill_bytes_correct() {
+        assert!(BytesTestStruct::dim() == 513 + 257 + 256);
+        let header = [1u8, 2];
+        let st = BytesTestStruct {
+            payload: vec![9; 10],
+            header: &header,
+            body: None,
+        };
+        let data = st.featurize();
+        assert!(data[9] == 1.0);
+        assert!(data[256] == 1.0);
+        assert!(data[512] == 10.0);
+        // Blocks keep the histogram, entropy, length order whatever the attribute order.
+        assert!(data[513 + 1] == 0.5 && data[513 + 2] == 0.5);
+        assert!(data[769] == 2.0);
+        assert!(data[770..] == [0.0; 256]);
+    }
+}
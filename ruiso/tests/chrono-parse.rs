@@ -0,0 +1,27 @@
+#![cfg(feature = "chrono")]
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use ruiso::*;
+
+#[derive(StructFeature)]
+pub struct ChronoTestStruct {
+    #[struct_feature(time(hour, weekday, weekend))]
+    at: DateTime<Utc>,
+    #[struct_feature(time(month, day))]
+    naive: Option<NaiveDateTime>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_chrono_correct() {
+        let at = DateTime::from_timestamp(1_600_000_000, 0).unwrap();
+        let st = ChronoTestStruct {
+            at,
+            naive: Some(at.naive_utc()),
+        };
+        assert!(st.featurize() == vec![12.0, 6.0, 1.0, 9.0, 13.0]);
+    }
+}
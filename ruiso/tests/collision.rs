@@ -0,0 +1,57 @@
+use ruiso::collision::{analyze, analyze_featurizer, hash_bucket};
+use ruiso::*;
+
+make_string_feature!(Name8, 8);
+
+#[derive(StructFeature)]
+pub struct HashedStruct {
+    #[struct_feature(dim = 13)]
+    kal: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<String> {
+        (0..200).map(|i| format!("value-{}", i)).collect()
+    }
+
+    #[test]
+    fn bucket_matches_derived_field() {
+        let st = HashedStruct {
+            kal: "hah".to_string(),
+        };
+        let data = st.featurize();
+        assert!(data[hash_bucket("hah", 13)] == 1.0);
+    }
+
+    #[test]
+    fn counts_distinct_values() {
+        let mut values = sample();
+        values.extend(sample());
+        let report = analyze(&values, 37, 0.01);
+        assert!(report.samples == 400);
+        assert!(report.distinct == 200);
+        assert!(report.buckets_used <= 37);
+        assert!(report.collision_rate == 1.0 - report.buckets_used as f64 / 200.0);
+        assert!(report.worst_buckets[0].1 >= report.worst_buckets.last().unwrap().1);
+    }
+
+    #[test]
+    fn featurizer_dimension_used() {
+        let values = sample();
+        let report = analyze_featurizer::<Name8, String, _>(&values, 0.05);
+        assert!(report.dim == 8);
+        assert!(report.buckets_used == 8);
+    }
+
+    #[test]
+    fn recommended_dim_meets_target() {
+        let values = sample();
+        let report = analyze(&values, 37, 0.05);
+        assert!(report.recommended_dim > 37);
+        let check = analyze(&values, report.recommended_dim, 0.05);
+        assert!(check.collision_rate <= 0.05);
+    }
+}
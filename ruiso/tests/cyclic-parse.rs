@@ -0,0 +1,63 @@
+use ruiso::*;
+
+make_cyclic_feature!(HourOfDay2, 24);
+
+#[derive(StructFeature)]
+pub struct CyclicTestStruct {
+    #[struct_feature(cyclic = 24)]
+    hour: u8,
+    #[struct_feature(cyclic = 360.0)]
+    bearing: Option<f32>,
+    #[struct_feature(cyclic = 7, default = 0.0)]
+    weekday: Option<u8>,
+    #[struct_feature(featurizer = "HourOfDay2")]
+    minute_hour: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: f32, b: f64) -> bool {
+        (a as f64 - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn dimension_correct() {
+        assert!(CyclicTestStruct::dim() == 8);
+    }
+
+    #[test]
+    fn fill_cyclic_correct() {
+        let st = CyclicTestStruct {
+            hour: 6,
+            bearing: Some(180.0),
+            weekday: None,
+            minute_hour: 18,
+        };
+        let data = st.featurize();
+        assert!(close(data[0], 1.0) && close(data[1], 0.0));
+        assert!(close(data[2], 0.0) && close(data[3], -1.0));
+        assert!(close(data[4], 0.0) && close(data[5], 1.0));
+        assert!(close(data[6], -1.0) && close(data[7], 0.0));
+    }
+
+    #[test]
+    fn missing_without_default() {
+        let st = CyclicTestStruct {
+            hour: 0,
+            bearing: None,
+            weekday: Some(0),
+            minute_hour: 0,
+        };
+        let data = st.featurize();
+        assert!(data[2] == 0.0 && data[3] == 0.0);
+    }
+
+    #[test]
+    fn standalone_any_numeric() {
+        let a = HourOfDay2::featurize(&23u8);
+        let b = HourOfDay2::featurize(&-1.0f64);
+        assert!(close(a[0], b[0] as f64) && close(a[1], b[1] as f64));
+    }
+}
@@ -0,0 +1,100 @@
+use ruiso::embedding::{Embeddable, EmbeddingTable};
+use ruiso::*;
+use std::io::Write;
+
+make_embedding_feature!(WordVec2, 2);
+make_embedding_feature!(NodeVec3, 3);
+
+pub enum Animals {
+    Cat,
+    Dog,
+}
+
+impl Embeddable for Animals {
+    fn embed(&self, table: &EmbeddingTable, slice: &mut [f32]) {
+        match self {
+            Animals::Cat => "cat",
+            Animals::Dog => "dog",
+        }
+        .embed(table, slice)
+    }
+}
+
+#[derive(StructFeature)]
+pub struct EmbeddingTestStruct {
+    #[struct_feature(featurizer = "WordVec2")]
+    word: String,
+    #[struct_feature(featurizer = "WordVec2")]
+    words: Vec<String>,
+    #[struct_feature(featurizer = "WordVec2")]
+    pet: Option<Animals>,
+    #[struct_feature(featurizer = "NodeVec3")]
+    node: u32,
+}
+
+const GLOVE: &str = "cat 1 0\ndog 0 1\nthe 0.5 0.5\n";
+const WORD2VEC: &str = "2 3\nfoo 1 2 3\nbar -1 -2 -3\n";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_text_formats() {
+        let glove = EmbeddingTable::read_text(GLOVE.as_bytes()).unwrap();
+        assert!(glove.dim == 2 && glove.len() == 3);
+        assert!(glove.token("dog") == [0.0, 1.0]);
+        assert!(glove.token("cow") == [0.0, 0.0]);
+        let w2v = EmbeddingTable::read_text(WORD2VEC.as_bytes()).unwrap();
+        assert!(w2v.dim == 3 && w2v.len() == 2);
+        assert!(w2v.token("bar") == [-1.0, -2.0, -3.0]);
+        assert!(EmbeddingTable::read_text("a 1 2\nb 3\n".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn read_binary_rows() {
+        let bytes: Vec<u8> = [1.0f32, 2.0, 3.0, 4.0, 5.0, 6.0]
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect();
+        let table = EmbeddingTable::read_binary(bytes.as_slice(), 3).unwrap();
+        assert!(table.row(1) == [4.0, 5.0, 6.0]);
+        assert!(table.row(2) == [0.0, 0.0, 0.0]);
+        assert!(EmbeddingTable::read_binary(bytes.as_slice(), 4).is_err());
+
+        let dir = std::env::temp_dir();
+        let vectors = dir.join("ruiso-embedding-test.bin");
+        let tokens = dir.join("ruiso-embedding-test.txt");
+        std::fs::write(&vectors, &bytes).unwrap();
+        std::fs::File::create(&tokens).unwrap().write_all(b"a\nb\n").unwrap();
+        let tokens = tokens.to_str().unwrap().to_string();
+        let table = EmbeddingTable::load_binary(&vectors, 3, Some(tokens)).unwrap();
+        assert!(table.token("b") == [4.0, 5.0, 6.0]);
+        let table = EmbeddingTable::load_binary(&vectors, 3, None::<&str>).unwrap();
+        assert!(table.row(0) == [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn fill_embedding_correct() {
+        assert!(WordVec2::install(EmbeddingTable::read_text(WORD2VEC.as_bytes()).unwrap()).is_err());
+        let table = EmbeddingTable::read_text(GLOVE.as_bytes()).unwrap().with_oov(vec![-1.0, -1.0]);
+        WordVec2::install(table).unwrap();
+        let bytes: Vec<u8> = (0..6).flat_map(|x| (x as f32).to_le_bytes()).collect();
+        NodeVec3::install(EmbeddingTable::read_binary(bytes.as_slice(), 3).unwrap()).unwrap();
+        assert!(EmbeddingTestStruct::dim() == 9);
+        let st = EmbeddingTestStruct {
+            word: "the".to_string(),
+            words: vec!["cat".to_string(), "dog".to_string(), "cow".to_string(), "cat".to_string()],
+            pet: Some(Animals::Dog),
+            node: 1,
+        };
+        assert!(st.featurize() == vec![0.5, 0.5, 0.25, 0.0, 0.0, 1.0, 3.0, 4.0, 5.0]);
+        let st = EmbeddingTestStruct {
+            word: "eel".to_string(),
+            words: vec![],
+            pet: Some(Animals::Cat),
+            node: 7,
+        };
+        assert!(st.featurize() == vec![-1.0, -1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,62 @@
+use ruiso::*;
+
+#[allow(dead_code)]
+#[derive(EnumFeature)]
+#[enum_feature(encoding = "discriminant")]
+#[repr(u8)]
+pub enum IpProtocol {
+    Icmp = 1,
+    Tcp = 6,
+    Udp = 17,
+}
+
+#[allow(dead_code)]
+#[derive(EnumFeature)]
+#[enum_feature(encoding = "one_hot_discriminant", range = [200, 205], missing_slot)]
+#[repr(u16)]
+pub enum Status {
+    Ok = 200,
+    Created,
+    NoContent = 204,
+    NotFound = 404,
+    #[enum_feature(skip)]
+    Teapot = 202,
+}
+
+#[derive(StructFeature)]
+pub struct DiscriminantTestStruct {
+    #[struct_feature(featurizer = "IpProtocolFeaturizer1")]
+    protocol: IpProtocol,
+    #[struct_feature(featurizer = "StatusFeaturizer6")]
+    status: Option<Status>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discriminant_column() {
+        assert!(IpProtocol::Udp.featurize() == vec![17.0]);
+        assert!(IpProtocol::Icmp.featurize() == vec![1.0]);
+    }
+
+    #[test]
+    fn one_hot_by_value() {
+        assert!(StatusFeaturizer6::dim() == 6);
+        assert!(Status::Ok.featurize() == vec![1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(Status::Created.featurize() == vec![0.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(Status::NoContent.featurize() == vec![0.0, 0.0, 0.0, 0.0, 1.0, 0.0]);
+        assert!(Status::NotFound.featurize() == vec![0.0; 6]);
+        assert!(Status::Teapot.featurize() == vec![0.0; 6]);
+    }
+
+    #[test]
+    fn fill_discriminant_struct() {
+        let st = DiscriminantTestStruct {
+            protocol: IpProtocol::Tcp,
+            status: None,
+        };
+        assert!(st.featurize() == vec![6.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
+    }
+}
@@ -0,0 +1,51 @@
+use ruiso::{EnumFeature, Featurizable, Featurizer};
+
+#[allow(dead_code)]
+#[derive(EnumFeature)]
+#[enum_feature(reserve = 8)]
+pub enum Protocol {
+    #[enum_feature(index = 3)]
+    Tcp,
+    Udp,
+    #[enum_feature(skip)]
+    Unknown,
+    #[enum_feature(index = 0)]
+    Icmp,
+    Gre,
+}
+
+#[allow(dead_code)]
+#[derive(EnumFeature)]
+pub enum Sparse {
+    First,
+    #[enum_feature(index = 4)]
+    Last,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reserved_dimension() {
+        assert!(ProtocolFeaturizer8::dim() == 8);
+        assert!(<Protocol as Featurizable>::dim() == 8);
+        assert!(SparseFeaturizer5::dim() == 5);
+    }
+
+    #[test]
+    fn explicit_slots() {
+        assert!(Protocol::Tcp.featurize() == vec![0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(Protocol::Icmp.featurize() == vec![1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(Protocol::Udp.featurize() == vec![0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(Protocol::Gre.featurize() == vec![0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(Sparse::Last.featurize() == vec![0.0, 0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn skipped_is_zero() {
+        let mut data = [0.0; 8];
+        ProtocolFeaturizer8::fill_slice(&Protocol::Unknown, &mut data);
+        assert!(data == [0.0; 8]);
+    }
+}
@@ -0,0 +1,56 @@
+use ruiso::*;
+
+#[allow(dead_code)]
+#[derive(EnumFeature)]
+#[enum_feature(missing_slot)]
+pub enum Animals {
+    Cat,
+    Dog,
+    #[enum_feature(skip)]
+    Other,
+}
+
+#[allow(dead_code)]
+#[derive(EnumFeature)]
+#[enum_feature(reserve = 4, missing_slot)]
+pub enum Colors {
+    Red,
+    Blue,
+}
+
+#[derive(StructFeature)]
+pub struct MissingEnumTestStruct {
+    foo: u8,
+    #[struct_feature(featurizer = "AnimalsFeaturizer3")]
+    pet: Option<Animals>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_slot_dimension() {
+        assert!(AnimalsFeaturizer3::dim() == 3);
+        assert!(ColorsFeaturizer5::dim() == 5);
+        let mut data = [0.0; 5];
+        ColorsFeaturizer5::default(&mut data);
+        assert!(data == [0.0, 0.0, 0.0, 0.0, 1.0]);
+    }
+
+    #[test]
+    fn missing_differs_from_skipped() {
+        let st = MissingEnumTestStruct { foo: 1, pet: None };
+        assert!(st.featurize() == vec![1.0, 0.0, 0.0, 1.0]);
+        let st = MissingEnumTestStruct {
+            foo: 1,
+            pet: Some(Animals::Other),
+        };
+        assert!(st.featurize() == vec![1.0, 0.0, 0.0, 0.0]);
+        let st = MissingEnumTestStruct {
+            foo: 1,
+            pet: Some(Animals::Dog),
+        };
+        assert!(st.featurize() == vec![1.0, 0.0, 1.0, 0.0]);
+    }
+}
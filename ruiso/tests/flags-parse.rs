@@ -0,0 +1,39 @@
+use ruiso::*;
+
+#[derive(StructFeature)]
+pub struct BitsTestStruct {
+    foo: u32,
+    #[struct_feature(bits = 8)]
+    tcp_flags: u8,
+    #[struct_feature(bits = 3)]
+    permissions: Option<u32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dimension_correct() {
+        assert!(BitsTestStruct::dim() == 12);
+        assert!(BitsTestStruct::feature_names()[..3] == ["foo", "tcp_flags[0]", "tcp_flags[1]"]);
+    }
+
+    #[test]
+    fn fill_bits_correct() {
+        let st = BitsTestStruct {
+            foo: 2,
+            tcp_flags: 0b0001_0010,
+            permissions: Some(0b1101),
+        };
+        let data = st.featurize();
+        assert!(data[1..9] == [0.0, 1.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+        assert!(data[9..12] == [1.0, 0.0, 1.0]);
+        let st = BitsTestStruct {
+            foo: 2,
+            tcp_flags: 0,
+            permissions: None,
+        };
+        assert!(st.featurize()[1..].iter().all(|x| *x == 0.0));
+    }
+}
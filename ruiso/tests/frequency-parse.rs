@@ -0,0 +1,89 @@
+use ruiso::frequency::{CategoryCounter, CountEncoding};
+use ruiso::*;
+
+#[derive(Hash)]
+pub enum Method {
+    Get,
+    Post,
+    Delete,
+}
+
+#[derive(StructFeature)]
+pub struct CountTestStruct {
+    #[struct_feature(count_encoding)]
+    domain: String,
+    #[struct_feature(count_encoding(frequency, unseen = -1))]
+    method: Method,
+    #[struct_feature(count_encoding(log), default = 0.5)]
+    port: Option<u16>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encode_counts() {
+        let mut counter = CategoryCounter::default();
+        for c in ["a", "a", "a", "b"] {
+            counter.observe(c);
+        }
+        assert!(counter.finish(false, false, 0.0).encode("a") == 3.0);
+        assert!(counter.finish(true, false, 0.0).encode("b") == 0.25);
+        assert!(counter.finish(false, true, 0.0).encode("a") == 4f64.ln());
+        assert!(counter.finish(false, false, -2.0).encode("c") == -2.0);
+    }
+
+    #[test]
+    fn encoding_round_trip() {
+        let mut counter = CategoryCounter::default();
+        for c in [3u64, 1, 3] {
+            counter.observe(&c);
+        }
+        counter.observe("two words");
+        counter.observe("back\\slash");
+        let encoding = counter.finish(true, true, -1.5);
+        let mut buf = Vec::new();
+        encoding.write_to(&mut buf).unwrap();
+        assert!(CountEncoding::read_from(&mut buf.as_slice()).unwrap() == encoding);
+        assert!(encoding.encode("two words") == (1.0f64 / 5.0).ln_1p());
+        // Tables saved without the fixed hash are refused.
+        let old = String::from_utf8(buf).unwrap().replacen("fnv1a ", "", 1);
+        assert!(CountEncoding::read_from(&mut old.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn fill_counts_correct() {
+        let data = vec![
+            CountTestStruct {
+                domain: "a.com".to_string(),
+                method: Method::Get,
+                port: Some(443),
+            },
+            CountTestStruct {
+                domain: "a.com".to_string(),
+                method: Method::Get,
+                port: None,
+            },
+            CountTestStruct {
+                domain: "b.com".to_string(),
+                method: Method::Post,
+                port: Some(443),
+            },
+            CountTestStruct {
+                domain: "a.com".to_string(),
+                method: Method::Get,
+                port: Some(80),
+            },
+        ];
+        CountTestStruct::install_fitted(CountTestStruct::fit(&data)).unwrap();
+        assert!(data[0].featurize() == vec![3.0, 0.75, 3f32.ln()]);
+        assert!(data[1].featurize() == vec![3.0, 0.75, 0.5]);
+        let unseen = CountTestStruct {
+            domain: "c.com".to_string(),
+            method: Method::Delete,
+            port: Some(22),
+        };
+        assert!(unseen.featurize() == vec![0.0, -1.0, 0.0]);
+    }
+}
@@ -0,0 +1,50 @@
+use ruiso::*;
+use std::collections::{BTreeSet, HashSet};
+
+#[derive(EnumFeature, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Animals {
+    Cat,
+    Dog,
+    Squirrel,
+    Eldritch,
+}
+
+#[derive(StructFeature)]
+pub struct MultiHotTestStruct {
+    #[struct_feature(featurizer = "AnimalsFeaturizer4", multi_hot)]
+    tags: Vec<Animals>,
+    #[struct_feature(featurizer = "AnimalsFeaturizer4", multi_hot = "count")]
+    seen: Vec<Animals>,
+    #[struct_feature(featurizer = "AnimalsFeaturizer4", multi_hot)]
+    pets: HashSet<Animals>,
+    #[struct_feature(featurizer = "AnimalsFeaturizer4", multi_hot = "count")]
+    kept: Option<BTreeSet<Animals>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_multi_hot_correct() {
+        assert!(MultiHotTestStruct::dim() == 16);
+        let st = MultiHotTestStruct {
+            tags: vec![Animals::Dog, Animals::Cat, Animals::Dog],
+            seen: vec![Animals::Dog, Animals::Cat, Animals::Dog],
+            pets: vec![Animals::Eldritch].into_iter().collect(),
+            kept: None,
+        };
+        let data = st.featurize();
+        assert!(data[0..4] == [1.0, 1.0, 0.0, 0.0]);
+        assert!(data[4..8] == [1.0, 2.0, 0.0, 0.0]);
+        assert!(data[8..12] == [0.0, 0.0, 0.0, 1.0]);
+        assert!(data[12..16] == [0.0; 4]);
+        let st = MultiHotTestStruct {
+            tags: vec![],
+            seen: vec![],
+            pets: HashSet::new(),
+            kept: Some(vec![Animals::Squirrel, Animals::Cat].into_iter().collect()),
+        };
+        assert!(st.featurize()[12..16] == [1.0, 0.0, 1.0, 0.0]);
+    }
+}
@@ -0,0 +1,63 @@
+use ruiso::collision::hash_bucket;
+use ruiso::ngram::NgramHasher;
+use ruiso::text::Tokenizer;
+use ruiso::*;
+
+make_ngram_feature!(Domain64, 64, NgramHasher::chars(2, 3).boundary());
+
+#[derive(StructFeature)]
+pub struct NgramTestStruct {
+    #[struct_feature(ngrams(chars, n = 3, dim = 128, binary))]
+    command: String,
+    #[struct_feature(ngrams(words, n = 2, tokenizer = "words", lowercase, boundary, dim = 32))]
+    agent: Option<String>,
+    #[struct_feature(featurizer = "Domain64")]
+    domain: String,
+}
+
+fn grams(hasher: &NgramHasher, text: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    hasher.for_each(text, |g| out.push(g.to_string()));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn char_ngrams() {
+        assert!(grams(&NgramHasher::chars(2, 3), "abcd") == vec!["ab", "bc", "cd", "abc", "bcd"]);
+        assert!(grams(&NgramHasher::chars(3, 3).boundary(), "ab") == vec!["<ab", "ab>"]);
+        assert!(grams(&NgramHasher::chars(3, 3), "ab").is_empty());
+        assert!(grams(&NgramHasher::chars(2, 2), "日本語") == vec!["日本", "本語"]);
+    }
+
+    #[test]
+    fn word_ngrams() {
+        let hasher = NgramHasher::words(Tokenizer::Whitespace, 1, 2).lowercase();
+        assert!(grams(&hasher, "Mozilla Gecko Firefox") == vec!["mozilla", "gecko", "firefox", "mozilla gecko", "gecko firefox"]);
+        let hasher = NgramHasher::words(Tokenizer::Whitespace, 2, 2).boundary();
+        assert!(grams(&hasher, "a b") == vec!["<s> a", "a b", "b </s>"]);
+    }
+
+    #[test]
+    fn fill_ngrams_correct() {
+        assert!(NgramTestStruct::dim() == 128 + 32 + 64);
+        let st = NgramTestStruct {
+            command: "aaaa".to_string(),
+            agent: Some("Curl Wget".to_string()),
+            domain: "ab".to_string(),
+        };
+        let data = st.featurize();
+        let mut expected = vec![0.0; 224];
+        expected[hash_bucket("aaa", 128)] = 1.0;
+        for g in ["<s> curl", "curl wget", "wget </s>"] {
+            expected[128 + hash_bucket(g, 32)] += 1.0;
+        }
+        for g in ["<a", "ab", "b>", "<ab", "ab>"] {
+            expected[160 + hash_bucket(g, 64)] += 1.0;
+        }
+        assert!(data == expected);
+    }
+}
@@ -0,0 +1,162 @@
+use ruiso::numeric::{split_int, SPLIT_PARTS};
+use ruiso::*;
+
+#[derive(StructFeature)]
+pub struct NonFiniteTestStruct {
+    #[struct_feature(non_finite = "zero")]
+    foo: f32,
+    #[struct_feature(non_finite = "default", default = 5.0)]
+    bar: Option<f64>,
+    #[struct_feature(non_finite = "clamp")]
+    kal: f64,
+    #[struct_feature(transform = "sqrt", non_finite = "zero")]
+    ell: f32,
+    kan: f32,
+}
+
+#[derive(StructFeature)]
+#[struct_feature(non_finite = "clamp")]
+pub struct WideNonFiniteTestStruct {
+    foo: f32,
+    #[struct_feature(non_finite = "error")]
+    bar: f64,
+}
+
+#[derive(StructFeature)]
+#[struct_feature(non_finite = "zero")]
+pub struct EncodedNonFiniteTestStruct {
+    #[struct_feature(cyclic = 24)]
+    hour: f32,
+    #[struct_feature(buckets = [1, 10])]
+    size: f64,
+    #[struct_feature(int_encoding = "split")]
+    id: u64,
+    #[struct_feature(bits = 2)]
+    mask: u8,
+}
+
+#[derive(StructFeature)]
+pub struct BucketNonFiniteTestStruct {
+    #[struct_feature(buckets = [1, 10], non_finite = "error")]
+    size: f64,
+}
+
+#[derive(StructFeature)]
+pub struct WideIntTestStruct {
+    #[struct_feature(int_encoding = "split")]
+    foo: u64,
+    #[struct_feature(int_encoding = "split")]
+    bar: Option<i64>,
+    #[struct_feature(int_encoding = "log")]
+    kal: i64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_finite_replaced() {
+        let st = NonFiniteTestStruct {
+            foo: f32::NAN,
+            bar: Some(f64::INFINITY),
+            kal: -1e300,
+            ell: -4.0,
+            kan: f32::INFINITY,
+        };
+        let data = st.featurize();
+        assert!(data[0] == 0.0);
+        assert!(data[1] == 5.0);
+        assert!(data[2] == f32::MIN);
+        assert!(data[3] == 0.0);
+        assert!(data[4] == f32::INFINITY);
+    }
+
+    #[test]
+    fn struct_wide_policy() {
+        let st = WideNonFiniteTestStruct {
+            foo: f32::NAN,
+            bar: 1.0,
+        };
+        assert!(st.featurize() == vec![0.0, 1.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn field_policy_overrides_struct() {
+        let st = WideNonFiniteTestStruct {
+            foo: 1.0,
+            bar: f64::NAN,
+        };
+        st.featurize();
+    }
+
+    #[test]
+    fn encoded_policy() {
+        let st = EncodedNonFiniteTestStruct {
+            hour: f32::NAN,
+            size: f64::INFINITY,
+            id: 1,
+            mask: 2,
+        };
+        let zero = EncodedNonFiniteTestStruct {
+            hour: 0.0,
+            size: 0.0,
+            id: 1,
+            mask: 2,
+        };
+        assert!(st.featurize() == zero.featurize());
+        assert!(st.featurize()[0..5] == [0.0, 1.0, 1.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bucket_policy_error() {
+        BucketNonFiniteTestStruct { size: f64::NAN }.featurize();
+    }
+
+    #[test]
+    fn split_exact() {
+        let join = |[high, mid, low]: [f32; SPLIT_PARTS]| {
+            assert!(high.fract() == 0.0 && mid.fract() == 0.0 && low.fract() == 0.0);
+            high as i128 * (1 << 48) + mid as i128 * (1 << 24) + low as i128
+        };
+        for x in [
+            0,
+            -1,
+            (1 << 47) + 12345,
+            u64::MAX as i128,
+            u64::MAX as i128 - 1,
+            i64::MIN as i128,
+            i64::MIN as i128 + 1,
+            i64::MAX as i128,
+        ] {
+            assert!(join(split_int(x)) == x);
+        }
+        assert!(split_int(-1) == [-1.0, ((1 << 24) - 1) as f32, ((1 << 24) - 1) as f32]);
+        assert!(split_int(u64::MAX as i128) == [((1 << 16) - 1) as f32, ((1 << 24) - 1) as f32, ((1 << 24) - 1) as f32]);
+        assert!(split_int(i64::MIN as i128) == [-(1 << 15) as f32, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn fill_wide_ints_correct() {
+        let st = WideIntTestStruct {
+            foo: (1 << 40) + 1,
+            bar: None,
+            kal: -1000,
+        };
+        let data = st.featurize();
+        assert!(WideIntTestStruct::dim() == 7);
+        assert!(data[..3] == [0.0, (1 << 16) as f32, 1.0]);
+        assert!(data[3..6] == [0.0; 3]);
+        assert!(data[6] == -(1001f64.ln()) as f32);
+        let st = WideIntTestStruct {
+            foo: u64::MAX,
+            bar: Some(i64::MIN),
+            kal: 0,
+        };
+        let data = st.featurize();
+        assert!(data[..3] == split_int(u64::MAX as i128));
+        assert!(data[3..6] == [-(1 << 15) as f32, 0.0, 0.0]);
+    }
+}
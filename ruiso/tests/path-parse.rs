@@ -0,0 +1,91 @@
+use ruiso::collision::hash_bucket;
+use ruiso::path::{PathFeaturizer, PATH_NAMES};
+use ruiso::vocab::Vocabulary;
+use ruiso::*;
+use std::path::PathBuf;
+
+make_path_feature!(Image29, 29, PathFeaturizer::new(8, 8, 8));
+
+#[derive(StructFeature)]
+pub struct PathTestStruct {
+    #[struct_feature(path(extension = 4, dirs = 4, basename = 4))]
+    image: String,
+    #[struct_feature(path(extensions("exe", ".DLL"), dirs = 2, basename = 2))]
+    target: Option<PathBuf>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_columns() {
+        assert!(PATH_NAMES.len() == 5);
+        let data = Image29::featurize(&"/tmp/.x/.payload.SH".to_string());
+        assert!(data[..5] == [3.0, 1.0, 1.0, 0.0, 0.0]);
+        assert!(data[5 + hash_bucket("sh", 8)] == 1.0);
+        assert!(data[13..21].iter().sum::<f32>() == 2.0);
+        assert!(data[13 + hash_bucket("tmp", 8)] >= 1.0);
+        assert!(data[21 + hash_bucket(".payload.SH", 8)] == 1.0);
+    }
+
+    #[test]
+    fn locations() {
+        let flags = |p: &str| Image29::featurize(&p.to_string())[..5].to_vec();
+        assert!(flags("/dev/shm/x") == vec![3.0, 0.0, 0.0, 1.0, 0.0]);
+        assert!(flags("/home/bob/.bashrc") == vec![3.0, 1.0, 0.0, 0.0, 1.0]);
+        assert!(flags("C:\\Users\\bob\\AppData\\Local\\Temp\\a.exe") == vec![6.0, 0.0, 1.0, 0.0, 1.0]);
+        assert!(flags("/tmpfiles/x") == vec![2.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(flags("relative/tmp/x") == vec![3.0, 0.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn no_extension() {
+        let data = Image29::featurize(&"/usr/bin/.bashrc".to_string());
+        assert!(data[5..13] == [0.0; 8]);
+        let data = Image29::featurize(&"/usr/bin/ls".to_string());
+        assert!(data[5..13] == [0.0; 8]);
+    }
+
+    #[test]
+    fn extension_vocab() {
+        let path = PathFeaturizer::new(1, 2, 2).extension_vocab(Vocabulary::new(vec!["exe", "dll"]));
+        assert!(path.dim() == 5 + 3 + 2 + 2);
+        let mut slice = vec![0.0; path.dim()];
+        path.fill("a.ps1", &mut slice);
+        assert!(slice[5..8] == [0.0, 0.0, 1.0]);
+        let names = path.names();
+        assert!(names.len() == path.dim());
+        assert!(names[..6] == ["depth", "hidden", "tmp", "dev_shm", "home", "ext:exe"]);
+        assert!(names[7..] == ["ext:other", "dirs[0]", "dirs[1]", "basename[0]", "basename[1]"]);
+    }
+
+    #[test]
+    fn path_names() {
+        let names = PathTestStruct::feature_names();
+        assert!(names.len() == PathTestStruct::dim());
+        assert!(names[0] == "image:depth");
+        assert!(names[5] == "image:ext[0]");
+        assert!(names[16] == "image:basename[3]");
+        assert!(names[22..25] == ["target:ext:exe", "target:ext:dll", "target:ext:other"]);
+    }
+
+    #[test]
+    fn fill_path_correct() {
+        assert!(PathTestStruct::dim() == 17 + 12);
+        let st = PathTestStruct {
+            image: "/bin/sh".to_string(),
+            target: Some(PathBuf::from("C:\\Windows\\evil.Dll")),
+        };
+        let data = st.featurize();
+        assert!(data[0] == 2.0);
+        assert!(data[5..9] == [0.0; 4]);
+        assert!(data[17..22] == [2.0, 0.0, 0.0, 0.0, 0.0]);
+        assert!(data[22..25] == [0.0, 1.0, 0.0]);
+        let st = PathTestStruct {
+            image: String::new(),
+            target: None,
+        };
+        assert!(st.featurize() == vec![0.0; 29]);
+    }
+}
@@ -0,0 +1,68 @@
+use ruiso::patterns::PatternMatcher;
+use ruiso::*;
+
+make_pattern_feature!(Suspicious2, 2, PatternMatcher::regex(vec!["^powershell", "base64"]).count());
+
+#[derive(StructFeature)]
+pub struct PatternTestStruct {
+    #[struct_feature(regex = ["^powershell", "base64"])]
+    command: String,
+    #[struct_feature(keywords = "tests/keywords.txt", matches = "count")]
+    args: Vec<String>,
+    #[struct_feature(keywords = ["aa", "a"])]
+    note: Option<String>,
+}
+
+#[derive(StructFeature)]
+pub struct RawPatternTestStruct {
+    #[struct_feature(keywords = ["tcp"])]
+    r#type: String,
+    port: u16,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standalone_counts() {
+        let data = Suspicious2::featurize(&"powershell base64 base64".to_string());
+        assert!(data == vec![1.0, 2.0]);
+        let data = Suspicious2::featurize(&"cmd /c powershell".to_string());
+        assert!(data == vec![0.0, 0.0]);
+    }
+
+    #[test]
+    fn overlapping_keywords() {
+        let matcher = PatternMatcher::keywords(vec!["aa", "a"]).count();
+        let mut slice = [0.0; 2];
+        matcher.fill("aaa", &mut slice);
+        assert!(slice == [2.0, 3.0]);
+    }
+
+    #[test]
+    fn feature_names() {
+        let names = PatternTestStruct::feature_names();
+        assert!(names[0..2] == ["command:^powershell", "command:base64"]);
+        assert!(names[2..5] == ["args:mimikatz", "args:-enc", "args:invoke-expression"]);
+        assert!(names[5..7] == ["note:aa", "note:a"]);
+        assert!(RawPatternTestStruct::feature_names() == ["type:tcp", "port"]);
+    }
+
+    #[test]
+    fn fill_patterns_correct() {
+        assert!(PatternTestStruct::dim() == 7);
+        let st = PatternTestStruct {
+            command: "powershell -enc ZWNobw== base64".to_string(),
+            args: vec!["-enc".to_string(), "x -enc mimikatz".to_string()],
+            note: None,
+        };
+        assert!(st.featurize() == vec![1.0, 1.0, 1.0, 2.0, 0.0, 0.0, 0.0]);
+        let st = PatternTestStruct {
+            command: "cmd".to_string(),
+            args: vec![],
+            note: Some("a".to_string()),
+        };
+        assert!(st.featurize() == vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
+    }
+}
@@ -0,0 +1,92 @@
+use ruiso::fitted::FittedField;
+use ruiso::scaling::StandardStats;
+use ruiso::*;
+
+#[derive(StructFeature)]
+pub struct ScaledTestStruct {
+    #[struct_feature(scale = "standard")]
+    foo: u32,
+    bar: f32,
+    #[struct_feature(scale = "standard")]
+    kal: Option<f64>,
+}
+
+#[derive(StructFeature)]
+pub struct SavedTestStruct {
+    #[struct_feature(scale = "standard")]
+    foo: i64,
+}
+
+#[derive(StructFeature)]
+pub struct UnfittedTestStruct {
+    #[struct_feature(scale = "standard")]
+    foo: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> Vec<ScaledTestStruct> {
+        vec![
+            ScaledTestStruct { foo: 1, bar: 10.0, kal: Some(2.0) },
+            ScaledTestStruct { foo: 3, bar: 20.0, kal: None },
+            ScaledTestStruct { foo: 5, bar: 30.0, kal: Some(4.0) },
+        ]
+    }
+
+    #[test]
+    fn fit_statistics_correct() {
+        let fitted = ScaledTestStruct::fit(&sample());
+        assert!(fitted.fields.len() == 2);
+        assert!(fitted.fields[0].0 == "foo");
+        let foo = fitted.standard(0);
+        assert!(foo.count == 3);
+        assert!(foo.mean == 3.0);
+        assert!((foo.std - (8.0f64 / 3.0).sqrt()).abs() < 1e-12);
+        let kal = fitted.standard(1);
+        assert!(kal.count == 2);
+        assert!(kal.mean == 3.0);
+        assert!(kal.std == 1.0);
+    }
+
+    #[test]
+    fn fill_scaled_correct() {
+        let data = sample();
+        ScaledTestStruct::install_fitted(ScaledTestStruct::fit(&data)).unwrap();
+        let features = data[2].featurize();
+        assert!((features[0] - (2.0 / (8.0f64 / 3.0).sqrt()) as f32).abs() < 1e-6);
+        assert!(features[1] == 30.0);
+        assert!(features[2] == 1.0);
+        let features = ScaledTestStructFeaturizer3::featurize(&data[1]);
+        assert!(features[0] == 0.0);
+        assert!(features[2] == 0.0);
+    }
+
+    #[test]
+    fn save_load_identical() {
+        let data: Vec<SavedTestStruct> = (0..10).map(|i| SavedTestStruct { foo: i * 7 - 3 }).collect();
+        let fitted = SavedTestStruct::fit(&data);
+        let path = std::env::temp_dir().join(format!("ruiso-scaling-{}.txt", std::process::id()));
+        fitted.save(&path).unwrap();
+        assert!(FittedStruct::load(&path).unwrap() == fitted);
+        SavedTestStruct::load_fitted(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let expected = fitted.standard(0).apply(11.0) as f32;
+        assert!(SavedTestStruct { foo: 11 }.featurize()[0] == expected);
+    }
+
+    #[test]
+    fn schema_mismatch_rejected() {
+        let fitted = FittedStruct {
+            fields: vec![("bar".to_string(), FittedField::Standard(StandardStats::default()))],
+        };
+        assert!(SavedTestStruct::install_fitted(fitted).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn unfitted_panics() {
+        UnfittedTestStruct { foo: 1 }.featurize();
+    }
+}
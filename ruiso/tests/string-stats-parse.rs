@@ -0,0 +1,71 @@
+use ruiso::collision::hash_bucket;
+use ruiso::string_stats::{longest_consonant_run, shannon_entropy, StringStats, STRING_STATS_NAMES};
+use ruiso::*;
+
+#[derive(StructFeature)]
+pub struct StatsTestStruct<'a> {
+    #[struct_feature(string_stats)]
+    domain: String,
+    #[struct_feature(string_stats(hash), dim = 5)]
+    user: Option<String>,
+    #[struct_feature(string_stats)]
+    tag: &'a str,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entropy_and_runs() {
+        assert!(shannon_entropy("") == 0.0);
+        assert!(shannon_entropy("aaaa") == 0.0);
+        assert!(shannon_entropy("abab") == 1.0);
+        assert!(shannon_entropy("abcd") == 2.0);
+        assert!(longest_consonant_run("xkcdqwerty") == 6);
+        assert!(longest_consonant_run("aeiou") == 0);
+    }
+
+    #[test]
+    fn stats_columns() {
+        assert!(<StringStats as Featurizer<String>>::dim() == STRING_STATS_NAMES.len());
+        let data = StringStats::featurize(&"Ab1!é");
+        assert!(data == vec![5.0, 5f32.log2(), 0.2, 0.2, 0.2, 0.2, 1.0, 5.0]);
+        assert!(StringStats::featurize(&"") == vec![0.0; 8]);
+        // Only ASCII digits count as digits.
+        assert!(StringStats::featurize(&"٣4")[2] == 0.5);
+    }
+
+    #[test]
+    fn fill_stats_correct() {
+        assert!(StatsTestStruct::dim() == 8 + 8 + 5 + 8);
+        let st = StatsTestStruct {
+            domain: "aa".to_string(),
+            user: Some("bb".to_string()),
+            tag: "",
+        };
+        let data = st.featurize();
+        assert!(data[0..8] == [2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 1.0]);
+        assert!(data[8..16] == [2.0, 0.0, 0.0, 0.0, 0.0, 0.0, 2.0, 1.0]);
+        let mut hashed = [0.0; 5];
+        hashed[hash_bucket("bb", 5)] = 1.0;
+        assert!(data[16..21] == hashed);
+        assert!(data[21..29] == [0.0; 8]);
+        let st = StatsTestStruct {
+            domain: String::new(),
+            user: None,
+            tag: "x",
+        };
+        assert!(st.featurize()[8..21] == [0.0; 13]);
+    }
+
+    #[test]
+    fn stats_names() {
+        let names = StatsTestStruct::feature_names();
+        assert!(names.len() == StatsTestStruct::dim());
+        assert!(names[0] == "domain:length");
+        assert!(names[15] == "user:distinct_chars");
+        assert!(names[16] == "user[0]");
+        assert!(names[21] == "tag:length");
+    }
+}
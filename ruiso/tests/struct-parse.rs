@@ -49,6 +49,7 @@ pub struct FeaturizerTestStruct {
     bar: Option<f32>,
 }
 
+#[allow(dead_code)]
 #[derive(StructFeature)]
 pub struct OffTestStruct {
     foo: u32,
@@ -66,6 +67,7 @@ pub enum Animals {
     Eldritch,
 }
 
+#[allow(dead_code)]
 #[derive(StructFeature)]
 pub struct EnumTestStruct {
     foo: u32,
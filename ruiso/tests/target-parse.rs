@@ -0,0 +1,189 @@
+use ruiso::target::{TargetEncoding, TargetStats};
+use ruiso::*;
+
+#[derive(StructFeature)]
+pub struct TargetTestStruct {
+    #[struct_feature(target(prior_weight = 2, min_count = 2))]
+    domain: String,
+    #[struct_feature(target, default = -1.0)]
+    asn: Option<u32>,
+    bar: u8,
+}
+
+#[derive(Hash)]
+pub enum Region {
+    East,
+    West,
+}
+
+#[derive(StructFeature)]
+pub struct EnumTargetTestStruct {
+    #[struct_feature(target(prior_weight = 0, min_count = 1))]
+    region: Region,
+    #[struct_feature(target(prior_weight = 0, min_count = 1))]
+    domain: String,
+}
+
+#[derive(StructFeature)]
+pub struct FoldTestStruct {
+    #[struct_feature(target(prior_weight = 1, min_count = 1))]
+    domain: String,
+    #[struct_feature(scale = "standard")]
+    size: f32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample() -> (Vec<TargetTestStruct>, Vec<f64>) {
+        let rows = [("a.com", 1.0), ("a.com", 1.0), ("b.com", 0.0), ("b.com", 0.0), ("c.com", 1.0)];
+        let data = rows
+            .iter()
+            .map(|(d, _)| TargetTestStruct {
+                domain: d.to_string(),
+                asn: None,
+                bar: 1,
+            })
+            .collect();
+        let labels = rows.iter().map(|(_, l)| *l).collect();
+        (data, labels)
+    }
+
+    #[test]
+    fn smoothed_means() {
+        let mut stats = TargetStats::default();
+        for (c, l) in [("x", 1.0), ("x", 1.0), ("y", 0.0), ("z", 0.0), ("z", f64::NAN)] {
+            stats.observe(c, l);
+        }
+        let encoding = stats.finish(2.0, 2);
+        assert!(encoding.prior == 0.5);
+        assert!(encoding.encode("x") == 0.75);
+        assert!(encoding.encode("y") == 0.5);
+        assert!(encoding.encode("unseen") == 0.5);
+    }
+
+    #[test]
+    fn encoding_round_trip() {
+        let mut stats = TargetStats::default();
+        for (c, l) in [(1u32, 0.25), (2, 3.0), (1, 1.0)] {
+            stats.observe(&c, l);
+        }
+        let encoding = stats.finish(1.0, 1);
+        let mut buf = Vec::new();
+        encoding.write_to(&mut buf).unwrap();
+        assert!(TargetEncoding::read_from(&mut buf.as_slice()).unwrap() == encoding);
+    }
+
+    #[test]
+    fn encoding_round_trip_strings() {
+        let mut stats = TargetStats::default();
+        for (c, l) in [("a b", 1.0), ("a\nb", 0.0), ("", 2.0)] {
+            stats.observe(c, l);
+        }
+        let encoding = stats.finish(0.0, 1);
+        let mut buf = Vec::new();
+        encoding.write_to(&mut buf).unwrap();
+        let read = TargetEncoding::read_from(&mut buf.as_slice()).unwrap();
+        assert!(read == encoding);
+        assert!(read.encode("a b") == 1.0);
+        assert!(read.encode("") == 2.0);
+        assert!(read.encode(&"a b".to_string()) == 1.0);
+        // Tables saved without the fixed hash are refused.
+        let old = String::from_utf8(buf).unwrap().replacen("fnv1a ", "", 1);
+        assert!(TargetEncoding::read_from(&mut old.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn out_of_fold_skips_own_label() {
+        let categories = ["a", "a", "b", "b"];
+        let labels = [1.0, 1.0, 0.0, 0.0];
+        let encoded = TargetEncoding::out_of_fold(&categories, &labels, 2, 0.0, 1);
+        // Fold 0 holds rows 0 and 2, fitted on rows 1 and 3.
+        assert!(encoded == vec![1.0, 1.0, 0.0, 0.0]);
+        let single = ["a", "b"];
+        let encoded = TargetEncoding::out_of_fold(&single, &[1.0, 0.0], 2, 0.0, 1);
+        assert!(encoded == vec![0.0, 1.0]);
+    }
+
+    #[test]
+    fn fill_target_correct() {
+        let (data, labels) = sample();
+        let fitted = TargetTestStruct::fit_labeled(data.iter().zip(labels.iter().copied()));
+        assert!(fitted.target(0).prior == 0.6);
+        TargetTestStruct::install_fitted(fitted).unwrap();
+        assert!(TargetTestStruct::dim() == 3);
+        let a = data[0].featurize();
+        assert!(a[0] == ((2.0 + 1.2) / 4.0) as f32);
+        assert!(a[1] == -1.0);
+        assert!(a[2] == 1.0);
+        assert!(data[4].featurize()[0] == 0.6);
+    }
+
+    #[test]
+    fn fit_out_of_fold_per_fold() {
+        let (data, labels) = sample();
+        let fitted = TargetTestStruct::fit_out_of_fold(&data, &labels, 5);
+        assert!(fitted.len() == 5);
+        // Without its own row a.com is seen once, under min_count.
+        assert!(fitted[0].target(0).encode("a.com") == fitted[0].target(0).prior);
+        assert!(fitted[0].target(0).prior == 0.5);
+    }
+
+    #[test]
+    fn fill_enum_target() {
+        let data = [
+            EnumTargetTestStruct {
+                region: Region::East,
+                domain: "a.com".to_string(),
+            },
+            EnumTargetTestStruct {
+                region: Region::West,
+                domain: "a.com".to_string(),
+            },
+        ];
+        let fitted = EnumTargetTestStruct::fit_labeled(data.iter().zip([1.0, 3.0]));
+        assert!(fitted.target(0).encode(&Region::West) == 3.0);
+        EnumTargetTestStruct::install_fitted(fitted).unwrap();
+        assert!(data[0].featurize() == vec![1.0, 2.0]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn out_of_fold_needs_two_folds() {
+        let (data, labels) = sample();
+        TargetTestStruct::fit_out_of_fold(&data, &labels, 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn fit_needs_labels() {
+        let (data, _) = sample();
+        TargetTestStruct::fit(&data);
+    }
+
+    #[test]
+    fn featurize_out_of_fold_rows() {
+        let rows = [("a", 1.0, 1.0), ("a", 3.0, 0.0), ("b", 5.0, 1.0), ("b", 7.0, 0.0)];
+        let data: Vec<FoldTestStruct> = rows
+            .iter()
+            .map(|(d, s, _)| FoldTestStruct {
+                domain: d.to_string(),
+                size: *s,
+            })
+            .collect();
+        let labels: Vec<f64> = rows.iter().map(|(_, _, l)| *l).collect();
+        let states = FoldTestStruct::fit_out_of_fold(&data, &labels, 2);
+        let full = FoldTestStruct::fit_labeled(data.iter().zip(labels.iter().copied()));
+        // Only the target tables change between folds.
+        assert!(states.iter().all(|state| state.standard(1) == full.standard(1)));
+        let features = FoldTestStruct::featurize_out_of_fold(&data, &labels, 2);
+        // Fold 0 holds rows 0 and 2, fitted on the 0 labels of rows 1 and 3, fold 1 the other way round.
+        assert!(features[0][0] == 0.0);
+        assert!(features[1][0] == 1.0);
+        assert!(features[2][0] == 0.0);
+        assert!(features[3][0] == 1.0);
+        assert!(features[0][1] == data[0].featurize()[1]);
+        assert!(FoldTestStruct::ruiso_fitted().expect("FoldTestStruct").as_ref() == &full);
+    }
+}
@@ -0,0 +1,65 @@
+use ruiso::collision::hash_bucket;
+use ruiso::text::{TextHasher, Tokenizer};
+use ruiso::*;
+
+make_text_feature!(Body16, 16, TextHasher::new(Tokenizer::Words).lowercase());
+
+#[derive(StructFeature)]
+pub struct TextTestStruct {
+    #[struct_feature(text(tokenizer = "words", dim = 64, lowercase, stopwords("the", "a")))]
+    title: String,
+    #[struct_feature(text(dim = 32, min_len = 3, max_tokens = 2))]
+    command: Option<String>,
+    #[struct_feature(text(pattern = "[0-9]+", dim = 8))]
+    lines: Vec<String>,
+    #[struct_feature(featurizer = "Body16")]
+    body: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tokenizers_split() {
+        let text = "The cat's hat, 42 times!";
+        let ws: Vec<&str> = Tokenizer::Whitespace.split(text).collect();
+        assert!(ws == vec!["The", "cat's", "hat,", "42", "times!"]);
+        let words: Vec<&str> = Tokenizer::Words.split(text).collect();
+        assert!(words == vec!["The", "cat's", "hat", "42", "times"]);
+        let digits: Vec<&str> = Tokenizer::regex("[0-9]+").split(text).collect();
+        assert!(digits == vec!["42"]);
+    }
+
+    #[test]
+    fn tokens_cleaned() {
+        let hasher = TextHasher::new(Tokenizer::Words)
+            .lowercase()
+            .min_len(3)
+            .stopwords(vec!["the"])
+            .max_tokens(2);
+        let tokens: Vec<String> = hasher.tokens("The big dog is THE best dog").map(|t| t.into_owned()).collect();
+        assert!(tokens == vec!["big", "dog"]);
+    }
+
+    #[test]
+    fn fill_text_correct() {
+        assert!(TextTestStruct::dim() == 64 + 32 + 8 + 16);
+        let st = TextTestStruct {
+            title: "The Dog and a dog".to_string(),
+            command: Some("ls -la /tmp /var".to_string()),
+            lines: vec!["line 12".to_string(), "12 and 7".to_string()],
+            body: "Hello".to_string(),
+        };
+        let data = st.featurize();
+        let mut expected = vec![0.0; 120];
+        expected[hash_bucket("dog", 64)] += 2.0;
+        expected[hash_bucket("and", 64)] += 1.0;
+        expected[64 + hash_bucket("-la", 32)] += 1.0;
+        expected[64 + hash_bucket("/tmp", 32)] += 1.0;
+        expected[96 + hash_bucket("12", 8)] += 2.0;
+        expected[96 + hash_bucket("7", 8)] += 1.0;
+        expected[104 + hash_bucket("hello", 16)] += 1.0;
+        assert!(data == expected);
+    }
+}
@@ -0,0 +1,102 @@
+use ruiso::fitted::{stable_bucket, stable_hash};
+use ruiso::text::{TextHasher, Tokenizer};
+use ruiso::tfidf::{DocFreqs, TfIdf};
+use ruiso::*;
+
+make_tfidf_feature!(Body8, 8, TextHasher::new(Tokenizer::Words).lowercase());
+
+#[derive(StructFeature)]
+pub struct TfIdfTestStruct {
+    #[struct_feature(tfidf(top_k = 3, tokenizer = "words", lowercase))]
+    title: String,
+    #[struct_feature(tfidf(dim = 16, sublinear, l2))]
+    body: Option<String>,
+}
+
+fn idf(docs: f64, df: f64) -> f64 {
+    ((1.0 + docs) / (1.0 + df)).ln() + 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fitted_weights() {
+        let mut freqs = DocFreqs::vocab(2, 1);
+        freqs.observe(&["a", "b", "a"]);
+        freqs.observe(&["a", "c"]);
+        let tfidf = freqs.finish(false, false);
+        assert!(tfidf.dim() == 2);
+        assert!(tfidf.idf == vec![idf(2.0, 2.0), idf(2.0, 1.0)]);
+        let mut slice = [0.0; 2];
+        tfidf.fill(&["a", "a", "b", "z"], &mut slice);
+        assert!(slice == [(2.0 * idf(2.0, 2.0)) as f32, idf(2.0, 1.0) as f32]);
+
+        let tfidf = freqs.finish(true, true);
+        tfidf.fill(&["a", "a", "b"], &mut slice);
+        let norm: f32 = slice.iter().map(|x| x * x).sum();
+        assert!((norm - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn weights_round_trip() {
+        let mut freqs = DocFreqs::hashed(4);
+        freqs.observe(&["x", "y"]);
+        let tfidf = freqs.finish(true, false);
+        let mut buf = Vec::new();
+        tfidf.write_to(&mut buf).unwrap();
+        assert!(TfIdf::read_from(&mut buf.as_slice()).unwrap() == tfidf);
+        // Files from before the columns used a fixed hash are refused.
+        let old = String::from_utf8(buf).unwrap().replacen("hashed_fnv1a", "hashed", 1);
+        assert!(TfIdf::read_from(&mut old.as_bytes()).is_err());
+
+        let mut freqs = DocFreqs::vocab(3, 1);
+        freqs.observe(&["tab\tx", "y"]);
+        let tfidf = freqs.finish(false, true);
+        let mut buf = Vec::new();
+        tfidf.write_to(&mut buf).unwrap();
+        assert!(TfIdf::read_from(&mut buf.as_slice()).unwrap() == tfidf);
+    }
+
+    #[test]
+    fn stable_hash_fixed() {
+        assert!(stable_hash("") == 0xcbf2_9ce4_8422_2325);
+        assert!(stable_hash("a") == 0xaf63_dc4c_8601_ec8c);
+        assert!(stable_bucket("a", 16) == 0xc);
+    }
+
+    #[test]
+    fn standalone_featurizer() {
+        let docs = ["the cat", "the dog", "The end"];
+        Body8::install(Body8::fit(docs.iter(), DocFreqs::hashed(8), false, false)).unwrap();
+        assert!(Body8::install(Body8::fit(docs.iter(), DocFreqs::hashed(4), false, false)).is_err());
+        let data = Body8::featurize(&"cat");
+        assert!(data[stable_bucket("cat", 8)] >= idf(3.0, 1.0) as f32);
+    }
+
+    #[test]
+    fn fill_tfidf_correct() {
+        let data: Vec<TfIdfTestStruct> = ["Red fox", "red dog", "blue fox", "red"]
+            .iter()
+            .map(|t| TfIdfTestStruct {
+                title: t.to_string(),
+                body: None,
+            })
+            .collect();
+        let fitted = TfIdfTestStruct::fit(&data);
+        assert!(fitted.tfidf(0).columns != fitted.tfidf(1).columns);
+        TfIdfTestStruct::install_fitted(fitted).unwrap();
+        assert!(TfIdfTestStruct::dim() == 3 + 16);
+        let st = TfIdfTestStruct {
+            title: "red red fox".to_string(),
+            body: Some("a a".to_string()),
+        };
+        let out = st.featurize();
+        // red is in 3 documents, fox in 2 and blue and dog tie in 1, broken alphabetically.
+        assert!(out[0] == (2.0 * idf(4.0, 3.0)) as f32);
+        assert!(out[1] == idf(4.0, 2.0) as f32);
+        assert!(out[2] == 0.0);
+        assert!(out[3 + stable_bucket("a", 16)] == 1.0);
+    }
+}
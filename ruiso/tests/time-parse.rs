@@ -0,0 +1,81 @@
+use ruiso::numeric::split_int;
+use ruiso::time::civil_from_days;
+use ruiso::*;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+make_time_feature!(EventTime3, [Hour, Weekday, IsWeekend]);
+make_time_feature!(Seen4, [EpochSeconds, Age], 0, 1_600_000_060);
+
+#[derive(StructFeature)]
+pub struct TimeTestStruct {
+    foo: u32,
+    seen: SystemTime,
+    #[struct_feature(time(hour, epoch, age, utc_offset = -18000, reference = 1600003600))]
+    local: Option<SystemTime>,
+    #[struct_feature(featurizer = "EventTime3")]
+    other: SystemTime,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2020-09-13 12:26:40 UTC, a Sunday
+    fn sunday() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_600_000_000)
+    }
+
+    #[test]
+    fn civil_dates() {
+        assert!(civil_from_days(0) == (1970, 1, 1));
+        assert!(civil_from_days(-1) == (1969, 12, 31));
+        assert!(civil_from_days(18_321) == (2020, 2, 29));
+    }
+
+    #[test]
+    fn dimension_correct() {
+        assert!(TimeTestStruct::dim() == 1 + 5 + 5 + 3);
+        assert!(<Seen4 as Featurizer<SystemTime>>::dim() == 4);
+    }
+
+    #[test]
+    fn fill_time_correct() {
+        let st = TimeTestStruct {
+            foo: 2,
+            seen: sunday(),
+            local: Some(sunday()),
+            other: sunday() + Duration::from_secs(86_400),
+        };
+        let data = st.featurize();
+        assert!(data[1..6] == [12.0, 6.0, 9.0, 13.0, 1.0]);
+        assert!(data[6] == 7.0);
+        assert!(data[7..10] == split_int(1_600_000_000));
+        assert!(data[10] == 3600.0);
+        assert!(data[11..14] == [12.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn missing_time_zero() {
+        let st = TimeTestStruct {
+            foo: 2,
+            seen: UNIX_EPOCH,
+            local: None,
+            other: UNIX_EPOCH - Duration::from_secs(1),
+        };
+        let data = st.featurize();
+        assert!(data[1..6] == [0.0, 3.0, 1.0, 1.0, 0.0]);
+        assert!(data[6..11] == [0.0; 5]);
+        assert!(data[11..14] == [23.0, 2.0, 0.0]);
+    }
+
+    #[test]
+    fn epoch_seconds_exact() {
+        // One second apart, which a single f32 column can't tell.
+        let a = Seen4::featurize(&sunday());
+        let b = Seen4::featurize(&(sunday() + Duration::from_secs(1)));
+        assert!(a[..3] != b[..3]);
+        assert!(a[2] + 1.0 == b[2]);
+        assert!(a[3] == 60.0 && b[3] == 59.0);
+        assert!(Seen4::featurize(&sunday()) == a);
+    }
+}
@@ -0,0 +1,74 @@
+use ruiso::transform::signed_log;
+use ruiso::*;
+
+#[derive(StructFeature)]
+pub struct TransformTestStruct {
+    #[struct_feature(transform = "clip(0,1e6) | log1p")]
+    foo: u64,
+    #[struct_feature(scale = 0.001, offset = 1)]
+    bar: f32,
+    #[struct_feature(transform = "minmax(10, 20)", default = 5.0)]
+    kal: Option<u16>,
+    #[struct_feature(transform = "signed_log")]
+    ell: i32,
+    #[struct_feature(transform = "offset(1) | sqrt", scale = 2.0)]
+    kan: f64,
+}
+
+#[derive(StructFeature)]
+pub struct TransformScaledTestStruct {
+    #[struct_feature(transform = "log1p", scale = "standard")]
+    foo: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transforms_applied_in_order() {
+        let st = TransformTestStruct {
+            foo: 5_000_000,
+            bar: 2000.0,
+            kal: Some(15),
+            ell: -10,
+            kan: 8.0,
+        };
+        let data = st.featurize();
+        assert!(data[0] == 1e6f64.ln_1p() as f32);
+        assert!(data[1] == 3.0);
+        assert!(data[2] == 0.5);
+        assert!(data[3] == signed_log(-10.0) as f32);
+        assert!(data[3] < 0.0);
+        assert!(data[4] == 6.0);
+    }
+
+    #[test]
+    fn default_not_transformed() {
+        let st = TransformTestStruct {
+            foo: 0,
+            bar: 0.0,
+            kal: None,
+            ell: 0,
+            kan: 0.0,
+        };
+        let data = st.featurize();
+        assert!(data[0] == 0.0);
+        assert!(data[1] == 1.0);
+        assert!(data[2] == 5.0);
+        assert!(data[3] == 0.0);
+        assert!(data[4] == 2.0);
+    }
+
+    #[test]
+    fn scaling_fitted_after_transform() {
+        let data = vec![
+            TransformScaledTestStruct { foo: 0 },
+            TransformScaledTestStruct { foo: 9 },
+        ];
+        let fitted = TransformScaledTestStruct::fit(&data);
+        assert!((fitted.standard(0).mean - 10f64.ln() / 2.0).abs() < 1e-12);
+        TransformScaledTestStruct::install_fitted(fitted).unwrap();
+        assert!((data[1].featurize()[0] - 1.0).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,110 @@
+use ruiso::vocab::{has_duplicates, TokenCounter, Vocabulary};
+use ruiso::*;
+
+make_vocab_feature!(Protocol4, ["tcp", "udp", "icmp"]);
+
+#[derive(StructFeature)]
+pub struct VocabTestStruct {
+    foo: u32,
+    #[struct_feature(vocab = ["tcp", "udp", "icmp"])]
+    protocol: String,
+    #[struct_feature(vocab = ["a", "b"])]
+    flags: Option<Vec<String>>,
+}
+
+#[derive(StructFeature)]
+pub struct BorrowedVocabTestStruct<'a> {
+    #[struct_feature(vocab = ["tcp", "udp"])]
+    protocol: &'a str,
+    #[struct_feature(vocab = ["a", "b"])]
+    tags: Vec<&'a str>,
+}
+
+#[derive(StructFeature)]
+pub struct FittedVocabTestStruct {
+    #[struct_feature(vocab(top_k = 2, min_count = 2))]
+    service: String,
+    #[struct_feature(featurizer = "Protocol4")]
+    protocol: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fill_borrowed_vocab() {
+        let st = BorrowedVocabTestStruct {
+            protocol: "udp",
+            tags: vec!["b", "c"],
+        };
+        assert!(st.featurize() == vec![0.0, 1.0, 0.0, 0.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn fill_vocab_correct() {
+        assert!(VocabTestStruct::dim() == 1 + 4 + 3);
+        let st = VocabTestStruct {
+            foo: 1,
+            protocol: "udp".to_string(),
+            flags: Some(vec!["a".to_string(), "z".to_string(), "a".to_string()]),
+        };
+        assert!(st.featurize() == vec![1.0, 0.0, 1.0, 0.0, 0.0, 2.0, 0.0, 1.0]);
+        let st = VocabTestStruct {
+            foo: 1,
+            protocol: "gre".to_string(),
+            flags: None,
+        };
+        assert!(st.featurize() == vec![1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn duplicates_found() {
+        assert!(!has_duplicates(&["tcp", "udp", "tc"]));
+        assert!(has_duplicates(&["tcp", "udp", "tcp"]));
+        assert!(!has_duplicates(&[]));
+    }
+
+    #[test]
+    fn counter_ranks_by_frequency() {
+        let mut counter = TokenCounter::default();
+        for token in ["b", "a", "c", "a", "b", "d", "a"] {
+            counter.observe(token);
+        }
+        let vocab = counter.finish(3, 2);
+        assert!(vocab.tokens == vec!["a".to_string(), "b".to_string()]);
+        assert!(vocab.dim() == 4);
+        assert!(vocab.index("b") == 1);
+        assert!(vocab.index("c") == vocab.oov());
+    }
+
+    #[test]
+    fn vocab_round_trip() {
+        let vocab = Vocabulary::new(["tab\there", "line\nbreak", "back\\slash", "plain"]);
+        let mut buf = Vec::new();
+        vocab.write_to(&mut buf).unwrap();
+        let read = Vocabulary::read_from(&mut buf.as_slice()).unwrap();
+        assert!(read == vocab);
+        assert!(read.index("line\nbreak") == 1);
+    }
+
+    #[test]
+    fn fill_fitted_vocab_correct() {
+        let data: Vec<FittedVocabTestStruct> = ["http", "ssh", "http", "dns", "ssh", "http"]
+            .iter()
+            .map(|s| FittedVocabTestStruct {
+                service: s.to_string(),
+                protocol: "tcp".to_string(),
+            })
+            .collect();
+        let fitted = FittedVocabTestStruct::fit(&data);
+        let mut buf = Vec::new();
+        fitted.write_to(&mut buf).unwrap();
+        let fitted = FittedStruct::read_from(&mut buf.as_slice()).unwrap();
+        assert!(fitted.vocab(0).tokens == vec!["http".to_string(), "ssh".to_string()]);
+        FittedVocabTestStruct::install_fitted(fitted).unwrap();
+        assert!(FittedVocabTestStruct::dim() == 3 + 4);
+        assert!(data[1].featurize() == vec![0.0, 1.0, 0.0, 1.0, 0.0, 0.0, 0.0]);
+        assert!(data[3].featurize() == vec![0.0, 0.0, 1.0, 1.0, 0.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,83 @@
+use ruiso::collision::{hash_bucket, hash_value};
+use ruiso::*;
+use std::collections::HashMap;
+
+#[derive(StructFeature)]
+pub struct MapTestStruct {
+    foo: u32,
+    #[struct_feature(dim = 17)]
+    terms: HashMap<String, f32>,
+}
+
+#[derive(StructFeature)]
+pub struct PairTestStruct {
+    #[struct_feature(dim = 7)]
+    terms: Vec<(String, f32)>,
+}
+
+#[derive(StructFeature)]
+pub struct MaxTestStruct {
+    #[struct_feature(dim = 7, combine = "max_abs")]
+    terms: Option<Vec<(String, f32)>>,
+}
+
+#[derive(StructFeature)]
+pub struct SignedTestStruct {
+    #[struct_feature(dim = 29, signed)]
+    terms: HashMap<String, f64>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_weights_added() {
+        let mut terms = HashMap::new();
+        terms.insert("cat".to_string(), 0.5);
+        let st = MapTestStruct { foo: 2, terms };
+        let data = st.featurize();
+        assert!(MapTestStruct::dim() == 18);
+        assert!(data[0] == 2.0);
+        assert!(data[1 + hash_bucket("cat", 17)] == 0.5);
+        assert!(data.iter().sum::<f32>() == 2.5);
+    }
+
+    #[test]
+    fn pair_collisions_summed() {
+        let st = PairTestStruct {
+            terms: vec![("cat".to_string(), 0.5), ("cat".to_string(), 2.0)],
+        };
+        let data = st.featurize();
+        assert!(data[hash_bucket("cat", 7)] == 2.5);
+    }
+
+    #[test]
+    fn pair_collisions_max_abs() {
+        let st = MaxTestStruct {
+            terms: Some(vec![("cat".to_string(), 0.5), ("cat".to_string(), -2.0)]),
+        };
+        let data = st.featurize();
+        assert!(data[hash_bucket("cat", 7)] == -2.0);
+        let st = MaxTestStruct {
+            terms: Some(vec![("cat".to_string(), -2.0), ("cat".to_string(), 2.0)]),
+        };
+        assert!(st.featurize()[hash_bucket("cat", 7)] == 2.0);
+        let st = MaxTestStruct {
+            terms: Some(vec![("cat".to_string(), 2.0), ("cat".to_string(), -2.0)]),
+        };
+        assert!(st.featurize()[hash_bucket("cat", 7)] == 2.0);
+        let st = MaxTestStruct { terms: None };
+        assert!(st.featurize().iter().all(|x| *x == 0.0));
+    }
+
+    #[test]
+    fn signed_weights() {
+        let mut terms = HashMap::new();
+        terms.insert("cat".to_string(), 3.0);
+        let st = SignedTestStruct { terms };
+        let data = st.featurize();
+        let expected = if hash_value("cat") >> 63 == 1 { -3.0 } else { 3.0 };
+        assert!(data[hash_bucket("cat", 29)] == expected);
+    }
+}
@@ -19,13 +19,150 @@ use quote::quote;
 use regex::Regex;
 use syn::{parse_macro_input, DeriveInput, Ident, Type};
 
-fn set_value_enum(name: &syn::Ident, i: usize, variant: &syn::Variant) -> proc_macro2::TokenStream {
+fn set_value_enum(name: &syn::Ident, i: Option<usize>, variant: &syn::Variant) -> proc_macro2::TokenStream {
     let v_name = &variant.ident;
-    quote! {
-        #name::#v_name => slice[#i] = 1.0
+    match i {
+        Some(i) => quote! {
+            #name::#v_name => slice[#i] = 1.0
+        },
+        None => quote! {
+            #name::#v_name => {}
+        },
+    }
+}
+
+fn set_discriminant_enum(
+    name: &syn::Ident,
+    range: Option<(i128, i128)>,
+    skip: bool,
+    variant: &syn::Variant,
+) -> proc_macro2::TokenStream {
+    let v_name = &variant.ident;
+    if skip {
+        return quote! {
+            #name::#v_name => {}
+        };
+    }
+    match range {
+        Some((lo, hi)) => quote! {
+            #name::#v_name => {
+                let d = #name::#v_name as i128;
+                if (#lo..#hi).contains(&d) {
+                    slice[(d - #lo) as usize] = 1.0;
+                }
+            }
+        },
+        None => quote! {
+            #name::#v_name => slice[0] = #name::#v_name as i128 as f32
+        },
     }
 }
 
+// `encoding = "discriminant"` gives `Some(None)`, `encoding = "one_hot_discriminant", range = [lo, hi]` gives `Some(Some((lo, hi)))`.
+fn enum_encoding_handler(metas: &[syn::Meta]) -> Option<Option<(i128, i128)>> {
+    let encoding = metas.iter().find_map(|meta| match meta {
+        syn::Meta::NameValue(mv) if mv.path.is_ident("encoding") => match &mv.lit {
+            syn::Lit::Str(v) => Some(v.value()),
+            _ => panic!("encoding should be a string"),
+        },
+        _ => None,
+    });
+    let range = metas.iter().find_map(|meta| match meta {
+        syn::Meta::List(ml) if ml.path.is_ident("range") => {
+            let bounds: Vec<i128> = ml
+                .nested
+                .iter()
+                .map(|n| match n {
+                    syn::NestedMeta::Lit(syn::Lit::Int(v)) => v.base10_parse().unwrap(),
+                    _ => panic!("range should be two integers"),
+                })
+                .collect();
+            match bounds.as_slice() {
+                [lo, hi] if lo < hi => Some((*lo, *hi)),
+                _ => panic!("range should be [low, high] with low < high, found {:?}", bounds),
+            }
+        }
+        _ => None,
+    });
+    match encoding.as_deref() {
+        None | Some("one_hot") => {
+            if range.is_some() {
+                panic!("range needs encoding = \"one_hot_discriminant\"");
+            }
+            None
+        }
+        Some("discriminant") => {
+            if range.is_some() {
+                panic!("range needs encoding = \"one_hot_discriminant\"");
+            }
+            Some(None)
+        }
+        Some("one_hot_discriminant") => match range {
+            Some(range) => Some(Some(range)),
+            None => panic!("encoding = \"one_hot_discriminant\" needs range = [low, high]"),
+        },
+        Some(other) => panic!("Unknown enum encoding {:?}", other),
+    }
+}
+
+// Slot of every variant, `None` for skipped ones. Explicit indices are kept and the other
+// variants take the lowest free slots in declaration order.
+fn enum_slots(variants: &[&syn::Variant]) -> Vec<Option<usize>> {
+    let mut explicit: Vec<Option<usize>> = Vec::with_capacity(variants.len());
+    let mut taken = std::collections::HashMap::new();
+    for v in variants {
+        let metas = attr_metas(&v.attrs, "enum_feature");
+        let index = metas.iter().find_map(|meta| match meta {
+            syn::Meta::NameValue(mv) if mv.path.is_ident("index") => match &mv.lit {
+                syn::Lit::Int(n) => Some(n.base10_parse::<usize>().unwrap()),
+                _ => panic!("index of {} should be an integer", v.ident),
+            },
+            _ => None,
+        });
+        if let Some(index) = index {
+            if let Some(other) = taken.insert(index, v.ident.to_string()) {
+                panic!("{} and {} both have enum_feature index {}", other, v.ident, index);
+            }
+        }
+        explicit.push(index);
+    }
+    let mut next = 0;
+    variants
+        .iter()
+        .zip(explicit)
+        .map(|(v, index)| {
+            let skip = attr_metas(&v.attrs, "enum_feature").iter().any(|meta| match meta {
+                syn::Meta::Path(p) => p.is_ident("skip"),
+                _ => false,
+            });
+            if skip {
+                if index.is_some() {
+                    panic!("{} can't be skipped and have an index", v.ident);
+                }
+                return None;
+            }
+            if index.is_some() {
+                return index;
+            }
+            while taken.contains_key(&next) {
+                next += 1;
+            }
+            taken.insert(next, v.ident.to_string());
+            Some(next)
+        })
+        .collect()
+}
+
+fn reserve_handler(metas: &[syn::Meta]) -> Option<usize> {
+    metas.iter().find_map(|meta| match meta {
+        syn::Meta::NameValue(mv) if mv.path.is_ident("reserve") => match &mv.lit {
+            syn::Lit::Int(n) => Some(n.base10_parse().unwrap()),
+            _ => panic!("reserve should be the number of slots"),
+        },
+        _ => None,
+    })
+}
+
 /*
 fn set_value_one_enum(name: &syn::Ident, i: usize, variant: &syn::Variant) -> proc_macro2::TokenStream {
     let v_name = &variant.ident;
@@ -39,8 +176,9 @@ fn set_value_one_enum(name: &syn::Ident, i: usize, variant: &syn::Variant) -> pr
 /// Produces a featurizer for your enum that encodes the enum in a one hot manner.
 /// This is named ____FeaturizerN where N is the number of attributes in the enum.
 /// For example:
-/// ```rust
-/// ---
+/// ```
+/// use ruiso::*;
+///
 /// #[derive(EnumFeature)]
 /// pub enum ExampleEnum {
 ///     Foo,
@@ -48,28 +186,88 @@ fn set_value_one_enum(name: &syn::Ident, i: usize, variant: &syn::Variant) -> pr
 ///     Kal,
 ///     Ell,
 /// }
+/// # assert!(ExampleEnumFeaturizer4::featurize(&ExampleEnum::Kal) == vec![0.0, 0.0, 1.0, 0.0]);
 /// ```
-/// produces ExampleEnumFeaturization4
+/// produces ExampleEnumFeaturizer4
+///
+/// Variants take their slot in declaration order, so adding or moving a variant shifts the columns after it.
+/// Pin a variant to a slot with `#[enum_feature(index = 3)]`, the other variants fill the lowest free slots,
+/// and reserve room for future variants with `#[enum_feature(reserve = 16)]` on the enum, which fixes the dimension.
+/// A variant marked `#[enum_feature(skip)]` takes no slot and is encoded as all zeros.
+/// Two variants with the same index fail to compile.
+/// With `#[enum_feature(missing_slot)]` on the enum, one more column is added after the variants and
+/// `default` sets it, so a missing `Option<ExampleEnum>` field doesn't look like a skipped variant.
+/// Fieldless enums with explicit discriminants, like `#[repr(u8)]` protocol numbers, can follow their values
+/// instead of the source order. `#[enum_feature(encoding = "discriminant")]` writes the discriminant in one column,
+/// and `#[enum_feature(encoding = "one_hot_discriminant", range = [0, 256])]` one hot encodes it with a column
+/// per value from the low bound up to, not including, the high bound. Values outside the range are all zeros.
 ///
 #[proc_macro_derive(EnumFeature, attributes(enum_feature))]
 pub fn derive_enum(input: TokenStream) -> TokenStream {
     let input = parse_macro_input! {input as DeriveInput};
-    let variants = match &input.data {
-        syn::Data::Enum(d) => &d.variants,
+    let variants: Vec<&syn::Variant> = match &input.data {
+        syn::Data::Enum(d) => d.variants.iter().collect(),
         _ => panic!("Need a enum"),
     };
-    let dim = variants.len();
-
     let enum_name = &input.ident;
+    let metas = attr_metas(&input.attrs, "enum_feature");
+    let slots = enum_slots(&variants);
+    let (dim, variant_setters): (usize, Vec<proc_macro2::TokenStream>) = match enum_encoding_handler(&metas) {
+        Some(range) => {
+            if reserve_handler(&metas).is_some() {
+                panic!("reserve only applies to the one hot encoding of {}", enum_name);
+            }
+            let setters = variants
+                .iter()
+                .zip(slots)
+                .map(|(v, slot)| {
+                    let indexed = attr_metas(&v.attrs, "enum_feature").iter().any(|meta| match meta {
+                        syn::Meta::NameValue(mv) => mv.path.is_ident("index"),
+                        _ => false,
+                    });
+                    if indexed {
+                        panic!("index only applies to the one hot encoding of {}", enum_name);
+                    }
+                    set_discriminant_enum(enum_name, range, slot.is_none(), v)
+                })
+                .collect();
+            let dim = match range {
+                Some((lo, hi)) => (hi - lo) as usize,
+                None => 1,
+            };
+            (dim, setters)
+        }
+        None => {
+            let used = slots.iter().flatten().map(|s| s + 1).max().unwrap_or(0);
+            let dim = match reserve_handler(&metas) {
+                Some(reserve) if reserve < used => {
+                    panic!("{} needs {} slots but only reserves {}", enum_name, used, reserve)
+                }
+                Some(reserve) => reserve,
+                None => used,
+            };
+            let setters = variants
+                .iter()
+                .zip(slots)
+                .map(|(v, i)| set_value_enum(enum_name, i, v))
+                .collect();
+            (dim, setters)
+        }
+    };
+    let missing_slot = metas.iter().any(|meta| match meta {
+        syn::Meta::Path(p) => p.is_ident("missing_slot"),
+        _ => false,
+    });
+    let (dim, default_setter) = if missing_slot {
+        (dim + 1, quote! { fn default(slice: &mut [f32]) { slice[#dim] = 1.0; } })
+    } else {
+        (dim, quote! { fn default(_slice: &mut [f32]) {} })
+    };
+
     let featurizer_name = Ident::new(
         &(enum_name.to_string() + &format!("Featurizer{}", dim)),
         Span::call_site(),
     );
-    let variant_setters: Vec<proc_macro2::TokenStream> = variants
-        .iter()
-        .enumerate()
-        .map(|(i, v)| set_value_enum(&enum_name, i, v))
-        .collect();
     let variant_setters2 = variant_setters.clone();
 
     let trait_impl = quote! {
@@ -81,7 +279,7 @@ pub fn derive_enum(input: TokenStream) -> TokenStream {
                     #(#variant_setters),*
                 };
             }
-            fn default(_slice: &mut [f32]) {}
+            #default_setter
         }
         impl Featurizable for #enum_name {
             fn dim() -> usize {#dim}
@@ -90,7 +288,7 @@ pub fn derive_enum(input: TokenStream) -> TokenStream {
                     #(#variant_setters2),*
                 };
             }
-            fn default(_slice: &mut [f32]) {}
+            #default_setter
         }
     };
     TokenStream::from(trait_impl)
@@ -100,14 +298,16 @@ fn detect_optional(field: &syn::Field) -> bool {
     let f_type = &field.ty;
     let f_path = match &f_type {
         Type::Path(p) => &p.path,
+        Type::Reference(_) => return false,
         _ => panic!("should be a type"),
     };
-    (&f_path.segments[0].ident == "Option")
+    &f_path.segments[0].ident == "Option"
 }
 
 fn get_underlying_type_option(f_type: &syn::Type) -> &Type {
     let f_path = match &f_type {
         Type::Path(p) => &p.path,
+        Type::Reference(_) => return f_type,
         _ => panic!("should be a type"),
     };
     if &f_path.segments[0].ident == "Option" {
@@ -126,43 +326,826 @@ fn get_underlying_type_option(f_type: &syn::Type) -> &Type {
     }
 }
 
+// Reads `name = [a, b]` as `name(a, b)` so lists of literals can be given as arrays.
+fn parse_option(input: syn::parse::ParseStream) -> syn::Result<syn::Meta> {
+    let fork = input.fork();
+    if fork.parse::<syn::Path>().is_ok() && fork.peek(syn::Token![=]) && fork.peek2(syn::token::Bracket) {
+        let path: syn::Path = input.parse()?;
+        input.parse::<syn::Token![=]>()?;
+        let content;
+        syn::bracketed!(content in input);
+        let lits = content.parse_terminated::<syn::Lit, syn::Token![,]>(syn::parse::Parse::parse)?;
+        Ok(syn::Meta::List(syn::MetaList {
+            path,
+            paren_token: Default::default(),
+            nested: lits.into_iter().map(syn::NestedMeta::Lit).collect(),
+        }))
+    } else {
+        input.parse()
+    }
+}
+
+fn field_metas(field: &syn::Field) -> Vec<syn::Meta> {
+    attr_metas(&field.attrs, "struct_feature")
+}
+
+fn attr_metas(attrs: &[syn::Attribute], path: &str) -> Vec<syn::Meta> {
+    let mut metas = Vec::new();
+    for attr in attrs.iter().filter(|a| a.path.is_ident(path)) {
+        let options = attr
+            .parse_args_with(|input: syn::parse::ParseStream| {
+                input.parse_terminated::<syn::Meta, syn::Token![,]>(parse_option)
+            })
+            .unwrap();
+        metas.extend(options);
+    }
+    metas
+}
+
+fn nested_handler(field: &syn::Field, name: &str) -> Option<Vec<syn::NestedMeta>> {
+    field_metas(field).into_iter().find_map(|meta| match meta {
+        syn::Meta::List(ml) if ml.path.is_ident(name) => Some(ml.nested.into_iter().collect()),
+        _ => None,
+    })
+}
+
+fn list_handler(field: &syn::Field, name: &str) -> Option<Vec<syn::Lit>> {
+    nested_handler(field, name).map(|nested| {
+        nested
+            .into_iter()
+            .map(|n| match n {
+                syn::NestedMeta::Lit(l) => l,
+                _ => panic!("{} should be a list of literals", name),
+            })
+            .collect()
+    })
+}
+
+fn name_value_handler(field: &syn::Field, name: &str) -> Option<syn::Lit> {
+    field_metas(field).into_iter().find_map(|meta| match meta {
+        syn::Meta::NameValue(mv) if mv.path.is_ident(name) => Some(mv.lit),
+        _ => None,
+    })
+}
+
+fn detect_flag(field: &syn::Field, name: &str) -> bool {
+    field_metas(field).iter().any(|meta| match meta {
+        syn::Meta::Path(pat) => pat.is_ident(name),
+        _ => false,
+    })
+}
+
 fn default_field_handler(field: &syn::Field) -> Option<syn::LitFloat> {
-    if let Some(attr) = field.attrs.first() {
-        let meta = match attr.parse_meta().unwrap() {
-            syn::Meta::List(ml) => match &ml.nested[0] {
-                syn::NestedMeta::Meta(m) => m.clone(),
-                _ => panic!("meta is not a list"),
-            },
-            _ => panic!("meta is not a list"),
-        };
+    match name_value_handler(field, "default") {
+        Some(syn::Lit::Float(v)) => Some(v),
+        _ => None,
+    }
+}
+
+fn buckets_handler(field: &syn::Field) -> Option<Vec<f64>> {
+    list_handler(field, "buckets").map(|lits| {
+        let bounds: Vec<f64> = lits.iter().map(lit_number).collect();
+        if bounds.windows(2).any(|w| w[0] >= w[1]) {
+            panic!("buckets should be strictly increasing, found {:?}", bounds);
+        }
+        bounds
+    })
+}
+
+fn quantiles_handler(field: &syn::Field) -> Option<usize> {
+    match name_value_handler(field, "quantiles") {
+        Some(syn::Lit::Int(v)) => match v.base10_parse().unwrap() {
+            0 => panic!("quantiles needs at least one bin, for {}", field_label(field)),
+            bins => Some(bins),
+        },
+        Some(_) => panic!("quantiles should be the number of bins"),
+        None => None,
+    }
+}
+
+enum VocabOption {
+    Fixed(Vec<String>),
+    Fitted { top_k: usize, min_count: usize },
+}
+
+fn vocab_handler(field: &syn::Field) -> Option<VocabOption> {
+    let nested = nested_handler(field, "vocab")?;
+    if nested.iter().all(|n| matches!(n, syn::NestedMeta::Lit(_))) && !nested.is_empty() {
+        let tokens: Vec<String> = nested
+            .into_iter()
+            .map(|n| match n {
+                syn::NestedMeta::Lit(syn::Lit::Str(v)) => v.value(),
+                other => panic!("vocab tokens should be strings, found {:?}", other),
+            })
+            .collect();
+        for (j, token) in tokens.iter().enumerate() {
+            if tokens[..j].contains(token) {
+                panic!("{}: vocab token {:?} is listed twice", field_label(field), token);
+            }
+        }
+        return Some(VocabOption::Fixed(tokens));
+    }
+    let mut top_k = None;
+    let mut min_count = 1;
+    for n in nested {
+        match n {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) if mv.path.is_ident("top_k") => {
+                top_k = Some(lit_number(&mv.lit) as usize);
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) if mv.path.is_ident("min_count") => {
+                min_count = lit_number(&mv.lit) as usize;
+            }
+            other => panic!("Unknown vocab option {:?}", other),
+        }
+    }
+    match top_k {
+        Some(top_k) => Some(VocabOption::Fitted { top_k, min_count }),
+        None => panic!("A fitted vocab needs top_k, for {}", field_label(field)),
+    }
+}
+
+fn target_handler(field: &syn::Field) -> Option<(f64, u64)> {
+    let nested = if detect_flag(field, "target") {
+        Vec::new()
+    } else {
+        nested_handler(field, "target")?
+    };
+    let mut prior_weight = 10.0;
+    let mut min_count = 1;
+    for n in nested {
+        match n {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) if mv.path.is_ident("prior_weight") => {
+                prior_weight = lit_number(&mv.lit);
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) if mv.path.is_ident("min_count") => {
+                min_count = lit_number(&mv.lit) as u64;
+            }
+            other => panic!("Unknown target option {:?}", other),
+        }
+    }
+    Some((prior_weight, min_count))
+}
+
+fn count_encoding_handler(field: &syn::Field) -> Option<(bool, bool, f64)> {
+    let nested = if detect_flag(field, "count_encoding") {
+        Vec::new()
+    } else {
+        nested_handler(field, "count_encoding")?
+    };
+    let mut frequency = false;
+    let mut log = false;
+    let mut unseen = 0.0;
+    for n in nested {
+        match n {
+            syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("frequency") => frequency = true,
+            syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("log") => log = true,
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) if mv.path.is_ident("unseen") => {
+                unseen = lit_number(&mv.lit);
+            }
+            other => panic!("Unknown count_encoding option {:?}", other),
+        }
+    }
+    Some((frequency, log, unseen))
+}
+
+fn fitted_kind(field: &syn::Field) -> Option<&'static str> {
+    if nested_handler(field, "tfidf").is_some() {
+        return Some("tfidf");
+    }
+    if count_encoding_handler(field).is_some() {
+        return Some("counts");
+    }
+    if target_handler(field).is_some() {
+        return Some("target");
+    }
+    if let Some(VocabOption::Fitted { .. }) = vocab_handler(field) {
+        return Some("vocab");
+    }
+    let standard = match name_value_handler(field, "scale") {
+        Some(syn::Lit::Str(v)) => match v.value().as_str() {
+            "standard" => true,
+            other => panic!("Unknown scaling {:?}, only \"standard\" is fitted", other),
+        },
+        _ => false,
+    };
+    let quantiles = quantiles_handler(field).is_some();
+    if (standard || quantiles) && buckets_handler(field).is_some() || standard && quantiles {
+        panic!("Pick one of scale = \"standard\", buckets and quantiles for {}", field_label(field));
+    }
+    if standard {
+        Some("standard")
+    } else if quantiles {
+        Some("quantiles")
+    } else {
+        None
+    }
+}
+
+fn fitted_width(field: &syn::Field) -> usize {
+    match fitted_kind(field) {
+        Some("quantiles") => quantiles_handler(field).unwrap(),
+        Some("vocab") => match vocab_handler(field) {
+            Some(VocabOption::Fitted { top_k, .. }) => top_k + 1,
+            _ => unreachable!(),
+        },
+        Some("tfidf") => tfidf_width(field),
+        _ => 1,
+    }
+}
+
+fn lit_number(lit: &syn::Lit) -> f64 {
+    match lit {
+        syn::Lit::Float(v) => v.base10_parse().unwrap(),
+        syn::Lit::Int(v) => v.base10_parse().unwrap(),
+        _ => panic!("Expected a number, found {:?}", lit),
+    }
+}
+
+// Name of a field for error messages and generated names, without the `r#` of raw identifiers.
+fn field_label(field: &syn::Field) -> String {
+    syn::ext::IdentExt::unraw(field.ident.as_ref().unwrap()).to_string()
+}
+
+fn parse_transform(field: &syn::Field, step: &str) -> (String, Vec<f64>) {
+    let step = step.trim();
+    let (op, args) = match step.find('(') {
+        Some(open) => {
+            if !step.ends_with(')') {
+                panic!("Unclosed arguments in transform {:?}", step);
+            }
+            let args: Vec<f64> = step[open + 1..step.len() - 1]
+                .split(',')
+                .map(|a| match a.trim().parse::<f64>() {
+                    Ok(v) if v.is_finite() => v,
+                    _ => panic!("Bad argument {:?} in transform {:?}", a, step),
+                })
+                .collect();
+            (step[..open].trim(), args)
+        }
+        None => (step, Vec::new()),
+    };
+    let arity = match op {
+        "log1p" | "signed_log" | "sqrt" => 0,
+        "scale" | "offset" => 1,
+        "clip" | "minmax" => 2,
+        _ => panic!("Unknown transform {:?}", op),
+    };
+    if args.len() != arity {
+        panic!("Transform {:?} takes {} arguments", op, arity);
+    }
+    match op {
+        "clip" if args[0] > args[1] => {
+            panic!("{}: clip({}, {}) needs min <= max", field_label(field), args[0], args[1])
+        }
+        "minmax" if args[0] >= args[1] => {
+            panic!("{}: minmax({}, {}) needs min < max", field_label(field), args[0], args[1])
+        }
+        _ => {}
+    }
+    (op.to_string(), args)
+}
+
+fn transform_steps(field: &syn::Field) -> Vec<(String, Vec<f64>)> {
+    let mut steps = Vec::new();
+    for meta in field_metas(field) {
         if let syn::Meta::NameValue(mv) = meta {
-            if mv.path.is_ident("default") {
-                if let syn::Lit::Float(v) = mv.lit {
-                    Some(v)
-                } else {
-                    None
+            if mv.path.is_ident("transform") {
+                match &mv.lit {
+                    syn::Lit::Str(v) => steps.extend(v.value().split('|').map(|step| parse_transform(field, step))),
+                    _ => panic!("transform should be a string"),
                 }
-            } else {
-                None
+            } else if mv.path.is_ident("scale") {
+                if let syn::Lit::Str(_) = mv.lit {
+                    continue;
+                }
+                steps.push(("scale".to_string(), vec![lit_number(&mv.lit)]));
+            } else if mv.path.is_ident("offset") {
+                steps.push(("offset".to_string(), vec![lit_number(&mv.lit)]));
+            }
+        }
+    }
+    steps
+}
+
+fn basic_value(field: &syn::Field, x: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let mut v = quote! { (#x as f64) };
+    for (op, args) in transform_steps(field) {
+        let args: Vec<proc_macro2::Literal> = args.into_iter().map(proc_macro2::Literal::f64_suffixed).collect();
+        v = match op.as_str() {
+            "log1p" => quote! { ruiso::transform::log1p(#v) },
+            "signed_log" => quote! { ruiso::transform::signed_log(#v) },
+            "sqrt" => quote! { ruiso::transform::sqrt(#v) },
+            "scale" => quote! { ruiso::transform::scale(#v, #(#args),*) },
+            "offset" => quote! { ruiso::transform::offset(#v, #(#args),*) },
+            "clip" => quote! { ruiso::transform::clip(#v, #(#args),*) },
+            _ => quote! { ruiso::transform::min_max(#v, #(#args),*) },
+        };
+    }
+    v
+}
+
+fn set_bucket_field(name:&syn::Ident,i: usize, k: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    let field_name = &field.ident;
+    let (dim, boundaries) = match buckets_handler(field) {
+        Some(bounds) => (bounds.len() + 1, Some(bounds)),
+        None => (quantiles_handler(field).unwrap(), None),
+    };
+    let iplus = i + dim;
+    let setter = |x: proc_macro2::TokenStream| {
+        let v = policy_value(field, x);
+        let index = match &boundaries {
+            Some(bounds) => quote! { ruiso::bucket::bucket_index(#v, &[#(#bounds),*]) },
+            None => quote! { fitted.quantiles(#k).index(#v) },
+        };
+        quote! {
+            if let Some(b) = #index {
+                slice[(#i)+b] = 1.0;
+            }
+        }
+    };
+    let tokens = if detect_optional(field) {
+        let set = setter(quote! { x });
+        quote! {
+            if let Some(x) = #name.#field_name {
+                #set
+            }
+        }
+    } else {
+        setter(quote! { #name.#field_name })
+    };
+    (iplus, tokens)
+}
+
+fn non_finite_handler(metas: Vec<syn::Meta>) -> Option<String> {
+    metas.into_iter().find_map(|meta| match meta {
+        syn::Meta::NameValue(mv) if mv.path.is_ident("non_finite") => match mv.lit {
+            syn::Lit::Str(v) => match v.value().as_str() {
+                "zero" | "default" | "error" | "clamp" => Some(v.value()),
+                other => panic!("non_finite should be zero, default, error or clamp, not {:?}", other),
+            },
+            _ => panic!("non_finite should be a string"),
+        },
+        _ => None,
+    })
+}
+
+fn non_finite_policy(field: &syn::Field, v: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let field_str = field_label(field);
+    match non_finite_handler(field_metas(field)).as_deref() {
+        Some("zero") => quote! { ruiso::numeric::finite_or(#v, 0.0) },
+        Some("default") => match default_field_handler(field) {
+            Some(f) => quote! { ruiso::numeric::finite_or(#v, #f) },
+            None => quote! { ruiso::numeric::finite_or(#v, 0.0) },
+        },
+        Some("clamp") => quote! { ruiso::numeric::clamp_finite(#v) },
+        Some(_) => quote! { ruiso::numeric::expect_finite(#v, #field_str) },
+        None => v,
+    }
+}
+
+// Integer encodings never see NaN or infinities, so a non_finite policy on them is a mistake.
+fn integer_encoded(field: &syn::Field) -> bool {
+    bits_handler(field).is_some() || int_encoding_handler(field).is_some()
+}
+
+fn reject_non_finite(field: &syn::Field, encoding: &str) {
+    if non_finite_handler(field_metas(field)).is_some() {
+        panic!("{}: non_finite has no effect with {}, integers are always finite", field_label(field), encoding);
+    }
+}
+
+// The transformed value of a cyclic or bucketed field with its non_finite policy applied.
+fn policy_value(field: &syn::Field, x: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let v = basic_value(field, x);
+    if non_finite_handler(field_metas(field)).is_some() {
+        let v = non_finite_policy(field, quote! { (#v as f32) });
+        quote! { (#v as f64) }
+    } else {
+        v
+    }
+}
+
+fn int_encoding_handler(field: &syn::Field) -> Option<String> {
+    match name_value_handler(field, "int_encoding") {
+        Some(syn::Lit::Str(v)) => match v.value().as_str() {
+            "split" | "log" => Some(v.value()),
+            other => panic!("int_encoding should be split or log, not {:?}", other),
+        },
+        Some(_) => panic!("int_encoding should be a string"),
+        None => None,
+    }
+}
+
+fn set_wide_int_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    let field_name = &field.ident;
+    reject_non_finite(field, "int_encoding");
+    let split = int_encoding_handler(field).unwrap() == "split";
+    // Three columns, see `ruiso::numeric::SPLIT_PARTS`.
+    let iplus = if split { i + 3 } else { i + 1 };
+    let setter = |x: proc_macro2::TokenStream| {
+        if split {
+            quote! {
+                slice[#i..#iplus].copy_from_slice(&ruiso::numeric::split_int(#x as i128));
             }
         } else {
-            None
+            quote! {
+                slice[#i] = ruiso::transform::signed_log(#x as f64) as f32;
+            }
+        }
+    };
+    let tokens = if detect_optional(field) {
+        let set = setter(quote! { x });
+        quote! {
+            if let Some(x) = #name.#field_name {
+                #set
+            }
         }
     } else {
-        None
+        setter(quote! { #name.#field_name })
+    };
+    (iplus, tokens)
+}
+
+fn cyclic_handler(field: &syn::Field) -> Option<f64> {
+    name_value_handler(field, "cyclic").map(|lit| {
+        let period = lit_number(&lit);
+        if period <= 0.0 {
+            panic!("cyclic needs a positive period, found {}", period);
+        }
+        period
+    })
+}
+
+fn set_cyclic_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    let iplus = i + 2;
+    let field_name = &field.ident;
+    let period = cyclic_handler(field).unwrap();
+    let tokens = if detect_optional(field) {
+        let v = policy_value(field, quote! { x });
+        let missing = match default_field_handler(field) {
+            Some(f) => {
+                let d = policy_value(field, quote! { #f });
+                quote! {
+                    else {
+                        ruiso::cyclic::fill_cyclic(#d, #period, &mut slice[#i..#iplus]);
+                    }
+                }
+            }
+            None => quote! {},
+        };
+        quote! {
+            if let Some(x) = #name.#field_name {
+                ruiso::cyclic::fill_cyclic(#v, #period, &mut slice[#i..#iplus]);
+            } #missing
+        }
+    } else {
+        let v = policy_value(field, quote! { #name.#field_name });
+        quote! {
+            ruiso::cyclic::fill_cyclic(#v, #period, &mut slice[#i..#iplus]);
+        }
+    };
+    (iplus, tokens)
+}
+
+fn bits_handler(field: &syn::Field) -> Option<usize> {
+    match name_value_handler(field, "bits") {
+        Some(syn::Lit::Int(v)) => {
+            let n: usize = v.base10_parse().unwrap();
+            let width = match get_underlying_type_option(&field.ty) {
+                Type::Path(p) => match p.path.segments.last().unwrap().ident.to_string().as_str() {
+                    "u8" | "i8" => 8,
+                    "u16" | "i16" => 16,
+                    "u32" | "i32" => 32,
+                    "u64" | "i64" | "usize" => 64,
+                    _ => 0,
+                },
+                _ => 0,
+            };
+            if width == 0 {
+                panic!("{}: bits needs an integer field, use flags for bitflags types", field_label(field));
+            }
+            if n == 0 || n > width {
+                panic!("{}: bits should be between 1 and {} for this type, found {}", field_label(field), width, n);
+            }
+            Some(n)
+        }
+        Some(_) => panic!("bits should be the number of bits"),
+        None => None,
+    }
+}
+
+fn set_bits_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    reject_non_finite(field, "bits");
+    let n = bits_handler(field).unwrap();
+    let iplus = i + n;
+    let field_name = &field.ident;
+    let tokens = if detect_optional(field) {
+        quote! {
+            if let Some(x) = #name.#field_name {
+                ruiso::flags::fill_bits(x as u64, #n, &mut slice[#i..#iplus]);
+            }
+        }
+    } else {
+        quote! {
+            ruiso::flags::fill_bits(#name.#field_name as u64, #n, &mut slice[#i..#iplus]);
+        }
+    };
+    (iplus, tokens)
+}
+
+// (width, type) of a `flags = 4` field, a `bitflags` type with one column per named flag.
+// The width is declared so the featurizer name keeps its dimension, and checked against `T::FLAGS`.
+fn flags_handler(field: &syn::Field) -> Option<(usize, &Type)> {
+    if detect_flag(field, "flags") {
+        panic!("{}: flags needs the number of flags, like flags = 4", field_label(field));
+    }
+    let width = match name_value_handler(field, "flags")? {
+        syn::Lit::Int(v) => v.base10_parse::<usize>().unwrap(),
+        _ => panic!("{}: flags should be the number of flags, like flags = 4", field_label(field)),
+    };
+    if width == 0 {
+        panic!("{}: flags = 0 gives no columns", field_label(field));
     }
+    if name_value_handler(field, "bits").is_some() {
+        panic!("{}: pick one of flags and bits", field_label(field));
+    }
+    Some((width, get_underlying_type_option(&field.ty)))
+}
+
+fn set_flags_field(name: &syn::Ident, i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    let (width, f_type) = flags_handler(field).unwrap();
+    let iplus = i + width;
+    let field_name = &field.ident;
+    let message = format!("{}: flags = {} doesn't match the number of flags of its type", field_label(field), width);
+    let fill = if detect_optional(field) {
+        quote! {
+            if let Some(x) = &#name.#field_name {
+                ruiso::flags::fill_flags(x, &mut slice[#i..#iplus]);
+            }
+        }
+    } else {
+        quote! {
+            ruiso::flags::fill_flags(&#name.#field_name, &mut slice[#i..#iplus]);
+        }
+    };
+    let tokens = quote! {
+        const _: () = assert!(<#f_type as ruiso::flags::Flags>::FLAGS.len() == #width, #message);
+        #fill
+    };
+    (iplus, quote! { { #tokens } })
 }
 
-fn set_basic_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+// Pushes the names of a field's columns, `field` for a single column and `field[j]` for blocks,
+// unless the field names them.
+fn field_names(field: &syn::Field, width: usize) -> proc_macro2::TokenStream {
+    let label = field_label(field);
+    if let Some((_, f_type)) = flags_handler(field) {
+        return quote! {
+            names.extend(ruiso::flags::flag_names::<#f_type>().into_iter().map(|flag| format!("{}:{}", #label, flag)));
+        };
+    }
+    if let Some((_, featurizer)) = path_handler(field) {
+        return quote! {
+            names.extend(#featurizer.names().into_iter().map(|name| format!("{}:{}", #label, name)));
+        };
+    }
+    if let Some((_, patterns, _)) = patterns_handler(field) {
+        let names: Vec<String> = patterns.iter().map(|p| format!("{}:{}", label, p)).collect();
+        return quote! { names.extend([#(#names),*].iter().map(|name| name.to_string())); };
+    }
+    if string_stats_handler(field).is_some() {
+        let hashed = width - STRING_STATS_WIDTH;
+        return quote! {
+            names.extend(ruiso::string_stats::STRING_STATS_NAMES.iter().map(|stat| format!("{}:{}", #label, stat)));
+            names.extend((0..#hashed).map(|j| format!("{}[{}]", #label, j)));
+        };
+    }
+    if width == 1 {
+        quote! { names.push(#label.to_string()); }
+    } else {
+        quote! { names.extend((0..#width).map(|j| format!("{}[{}]", #label, j))); }
+    }
+}
+
+fn set_basic_field(name:&syn::Ident,i: usize, k: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    if bits_handler(field).is_some() {
+        return set_bits_field(name, i, field);
+    }
+    if cyclic_handler(field).is_some() {
+        return set_cyclic_field(name, i, field);
+    }
+    if buckets_handler(field).is_some() || quantiles_handler(field).is_some() {
+        return set_bucket_field(name, i, k, field);
+    }
+    if int_encoding_handler(field).is_some() {
+        return set_wide_int_field(name, i, field);
+    }
+    let iplus = i + 1;
+    let field_name = &field.ident;
+    let transformed = !transform_steps(field).is_empty();
+    let value = |x: proc_macro2::TokenStream| {
+        let v = match fitted_kind(field) {
+            Some(_) => {
+                let v = basic_value(field, x);
+                quote! { fitted.standard(#k).apply(#v) as f32 }
+            }
+            None if transformed => {
+                let v = basic_value(field, x);
+                quote! { #v as f32 }
+            }
+            None => quote! { #x as f32 },
+        };
+        non_finite_policy(field, v)
+    };
+    let tokens;
+    if detect_optional(field) {
+        let v = value(quote! { x });
+        match default_field_handler(field) {
+            Some(f) => {
+                tokens = quote! {
+                    if let Some(x) = #name.#field_name {
+                        slice[#i] = #v;
+                    } else {
+                        slice[#i] = #f;
+                    }
+                };
+            }
+            None => {
+                tokens = quote! {
+                    if let Some(x) = #name.#field_name {
+                        slice[#i] = #v;
+                    }
+                };
+            }
+        }
+    } else {
+        let v = value(quote! { #name.#field_name });
+        tokens = quote! {
+            slice[#i] = #v;
+        };
+    }
+    (iplus, tokens)
+}
+
+// Panics unless the field holds one string, for options whose columns describe a single string.
+fn expect_single_string(field: &syn::Field, option: &str) {
+    if let Type::Path(pat) = get_underlying_type_option(&field.ty) {
+        if pat.path.segments.last().unwrap().ident == "Vec" {
+            panic!("{}: {} needs a single string, not a Vec", field_label(field), option);
+        }
+    }
+}
+
+// Runs `body` once for every string of a `String`, `&str`, a `Vec` of them or their `Option`, bound to `s`,
+// a reference to the string.
+fn for_each_string(
+    name: &syn::Ident,
+    field: &syn::Field,
+    body: proc_macro2::TokenStream,
+) -> proc_macro2::TokenStream {
+    let field_name = &field.ident;
+    let is_vec = match get_underlying_type_option(&field.ty) {
+        Type::Path(pat) => pat.path.segments.last().unwrap().ident == "Vec",
+        _ => false,
+    };
+    let each = if is_vec {
+        quote! {
+            for s in x {
+                #body
+            }
+        }
+    } else {
+        quote! {
+            let s = x;
+            #body
+        }
+    };
+    if detect_optional(field) {
+        quote! {
+            if let Some(x) = &#name.#field_name {
+                #each
+            }
+        }
+    } else {
+        quote! {
+            let x = &#name.#field_name;
+            #each
+        }
+    }
+}
+
+fn fit_field(k: usize, field: &syn::Field) -> (proc_macro2::TokenStream, proc_macro2::TokenStream, proc_macro2::TokenStream) {
+    let field_name = &field.ident;
+    let field_str = field_label(field);
+    let acc = Ident::new(&format!("fit{}", k), Span::call_site());
+    let observe_value = |x: proc_macro2::TokenStream| {
+        let v = basic_value(field, x);
+        quote! { #acc.observe(#v); }
+    };
+    let data = syn::Ident::new("data", Span::call_site());
+    let observe = if fitted_kind(field) == Some("vocab") {
+        for_each_string(&data, field, quote! { #acc.observe(s); })
+    } else if fitted_kind(field) == Some("tfidf") {
+        let doc = tfidf_doc(&data, field);
+        quote! {
+            {
+                #doc
+                #acc.observe(&doc);
+            }
+        }
+    } else if fitted_kind(field) == Some("counts") {
+        if detect_optional(field) {
+            quote! {
+                if let Some(x) = &data.#field_name {
+                    #acc.observe(x);
+                }
+            }
+        } else {
+            quote! { #acc.observe(&data.#field_name); }
+        }
+    } else if fitted_kind(field) == Some("target") {
+        if detect_optional(field) {
+            quote! {
+                if let Some(x) = &data.#field_name {
+                    #acc.observe(x, label);
+                }
+            }
+        } else {
+            quote! { #acc.observe(&data.#field_name, label); }
+        }
+    } else if detect_optional(field) {
+        let o = observe_value(quote! { x });
+        quote! {
+            if let Some(x) = data.#field_name {
+                #o
+            }
+        }
+    } else {
+        observe_value(quote! { data.#field_name })
+    };
+    let (init, fitted) = match fitted_kind(field) {
+        Some("quantiles") => {
+            let bins = quantiles_handler(field).unwrap();
+            (
+                quote! { let mut #acc = ruiso::bucket::QuantileSketch::default(); },
+                quote! { ruiso::fitted::FittedField::Quantiles(#acc.finish(#bins)) },
+            )
+        }
+        Some("vocab") => match vocab_handler(field) {
+            Some(VocabOption::Fitted { top_k, min_count }) => (
+                quote! { let mut #acc = ruiso::vocab::TokenCounter::default(); },
+                quote! { ruiso::fitted::FittedField::Vocab(#acc.finish(#top_k, #min_count)) },
+            ),
+            _ => unreachable!(),
+        },
+        Some("tfidf") => {
+            let (columns, sublinear, l2, _) = tfidf_handler(field).unwrap();
+            let init = match columns {
+                TfIdfOption::Hashed(dim) => quote! { ruiso::tfidf::DocFreqs::hashed(#dim) },
+                TfIdfOption::Vocab { top_k, min_df } => quote! { ruiso::tfidf::DocFreqs::vocab(#top_k, #min_df) },
+            };
+            (
+                quote! { let mut #acc = #init; },
+                quote! { ruiso::fitted::FittedField::TfIdf(#acc.finish(#sublinear, #l2)) },
+            )
+        }
+        Some("counts") => {
+            let (frequency, log, unseen) = count_encoding_handler(field).unwrap();
+            (
+                quote! { let mut #acc = ruiso::frequency::CategoryCounter::default(); },
+                quote! { ruiso::fitted::FittedField::Counts(#acc.finish(#frequency, #log, #unseen)) },
+            )
+        }
+        Some("target") => {
+            let (prior_weight, min_count) = target_handler(field).unwrap();
+            (
+                quote! { let mut #acc = ruiso::target::TargetStats::default(); },
+                quote! { ruiso::fitted::FittedField::Target(#acc.finish(#prior_weight, #min_count)) },
+            )
+        }
+        _ => (
+            quote! { let mut #acc = ruiso::scaling::RunningStats::default(); },
+            quote! { ruiso::fitted::FittedField::Standard(#acc.finish()) },
+        ),
+    };
+    let entry = quote! {
+        (String::from(#field_str), #fitted)
+    };
+    (init, observe, entry)
+}
+
+fn set_bool_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
     let iplus = i + 1;
     let field_name = &field.ident;
     let tokens;
     if detect_optional(field) {
-        match default_field_handler(&field) {
+        match default_field_handler(field) {
             Some(f) => {
                 tokens = quote! {
                     if let Some(x) = #name.#field_name {
-                        slice[#i] = x as f32;
+                        if x {
+                            slice[#i] = 1.0;
+                        }
                     } else {
                         slice[#i] = #f;
                     }
@@ -171,110 +1154,685 @@ fn set_basic_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, pro
             None => {
                 tokens = quote! {
                     if let Some(x) = #name.#field_name {
-                        slice[#i] = x as f32;
+                        if x {
+                            slice[#i] = 1.0;
+                        }
                     }
                 };
             }
         }
-    } else {
-        tokens = quote! {
-            slice[#i] = #name.#field_name as f32;
-        };
+    } else {
+        tokens = quote! {
+            if &#name.#field_name {
+                slice[#i] = 1.0;
+            }
+        };
+    }
+    (iplus, tokens)
+}
+
+fn string_dimension_handler(field: &syn::Field) -> Option<syn::LitInt> {
+    match name_value_handler(field, "dim") {
+        Some(syn::Lit::Int(v)) => Some(v),
+        _ => None,
+    }
+}
+
+fn set_string_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    let dim: usize = match string_dimension_handler(field) {
+        Some(d) => d.base10_parse().unwrap(),
+        None => 37,
+    };
+    let iplus = i + dim;
+    let field_name = &field.ident;
+
+    let tokens = if detect_optional(field) {
+        quote! {
+            if let Some(x) = &#name.#field_name {
+                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                x.hash(&mut hasher);
+                let result = (hasher.finish() as usize) % #dim;
+                slice[(#i)+result] += 1.0;
+            }
+        }
+    } else {
+        quote! {
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            #name.#field_name.hash(&mut hasher);
+            let result = (hasher.finish() as usize) % #dim;
+            slice[(#i)+result] += 1.0;
+        }
+    };
+    (iplus, tokens)
+}
+
+fn set_vocab_field(name:&syn::Ident,i: usize, k: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    let (dim, column) = match vocab_handler(field).unwrap() {
+        VocabOption::Fixed(tokens) => {
+            let oov = tokens.len();
+            let arms = tokens.iter().enumerate().map(|(j, t)| quote! { #t => #j, });
+            (
+                oov + 1,
+                quote! {
+                    match AsRef::<str>::as_ref(s) {
+                        #(#arms)*
+                        _ => #oov,
+                    }
+                },
+            )
+        }
+        VocabOption::Fitted { top_k, .. } => (top_k + 1, quote! { fitted.vocab(#k).index(s) }),
+    };
+    let iplus = i + dim;
+    let tokens = for_each_string(
+        name,
+        field,
+        quote! {
+            slice[(#i) + #column] += 1.0;
+        },
+    );
+    (iplus, tokens)
+}
+
+// Target and count encoded fields both write `fitted.<accessor>(k).encode(x)`.
+fn set_encoded_field(name:&syn::Ident,i: usize, k: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    let accessor = Ident::new(fitted_kind(field).unwrap(), Span::call_site());
+    let iplus = i + 1;
+    let field_name = &field.ident;
+    let tokens = if detect_optional(field) {
+        let missing = match default_field_handler(field) {
+            Some(f) => quote! { slice[#i] = #f; },
+            None => quote! {},
+        };
+        quote! {
+            if let Some(x) = &#name.#field_name {
+                slice[#i] = fitted.#accessor(#k).encode(x) as f32;
+            } else {
+                #missing
+            }
+        }
+    } else {
+        quote! {
+            slice[#i] = fitted.#accessor(#k).encode(&#name.#field_name) as f32;
+        }
+    };
+    (iplus, tokens)
+}
+
+// Runs `body` with the bytes of a `Vec<u8>`, `&[u8]` or their `Option` bound to `b: &[u8]`.
+fn for_bytes(name: &syn::Ident, field: &syn::Field, body: proc_macro2::TokenStream) -> proc_macro2::TokenStream {
+    let field_name = &field.ident;
+    if detect_optional(field) {
+        quote! {
+            if let Some(x) = &#name.#field_name {
+                let b: &[u8] = x.as_ref();
+                #body
+            }
+        }
+    } else {
+        quote! {
+            let b: &[u8] = #name.#field_name.as_ref();
+            #body
+        }
+    }
+}
+
+// (histogram, entropy, length, window, step) of `bytes(histogram, entropy, length, window = 2048, step = 1024)`.
+type BytesBlocks = (bool, bool, bool, Option<usize>, Option<usize>);
+
+fn bytes_handler(field: &syn::Field) -> Option<BytesBlocks> {
+    let nested = if detect_flag(field, "bytes") {
+        Vec::new()
+    } else {
+        nested_handler(field, "bytes")?
+    };
+    let (mut histogram, mut entropy, mut length) = (false, false, false);
+    let (mut window, mut step) = (None, None);
+    for n in nested {
+        match n {
+            syn::NestedMeta::Meta(syn::Meta::Path(p)) => match p.get_ident().map(|p| p.to_string()).as_deref() {
+                Some("histogram") => histogram = true,
+                Some("entropy") => entropy = true,
+                Some("length") => length = true,
+                _ => panic!("Unknown bytes block {:?}", p.get_ident()),
+            },
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) if mv.path.is_ident("window") => {
+                window = Some(lit_number(&mv.lit) as usize);
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) if mv.path.is_ident("step") => {
+                step = Some(lit_number(&mv.lit) as usize);
+            }
+            other => panic!("Unknown bytes option {:?}", other),
+        }
+    }
+    if window == Some(0) || step == Some(0) {
+        panic!("{}: bytes window and step should be above 0", field_label(field));
+    }
+    if !(histogram || entropy || length) {
+        histogram = true;
+        entropy = true;
+        length = true;
+    }
+    Some((histogram, entropy, length, window, step))
+}
+
+fn set_bytes_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    let (histogram, entropy, length, window, step) = bytes_handler(field).unwrap();
+    let mut j = i;
+    let mut blocks = Vec::new();
+    if histogram {
+        let end = j + 256;
+        blocks.push(quote! { ruiso::bytes::fill_byte_histogram(b, &mut slice[#j..#end]); });
+        j = end;
+    }
+    if entropy {
+        let end = j + 256;
+        let window = match window {
+            Some(w) => quote! { #w },
+            None => quote! { ruiso::bytes::ENTROPY_WINDOW },
+        };
+        let step = match step {
+            Some(s) => quote! { #s },
+            None => quote! { ruiso::bytes::ENTROPY_STEP },
+        };
+        blocks.push(quote! { ruiso::bytes::fill_byte_entropy(b, #window, #step, &mut slice[#j..#end]); });
+        j = end;
+    }
+    if length {
+        blocks.push(quote! { slice[#j] = b.len() as f32; });
+        j += 1;
+    }
+    let tokens = for_bytes(name, field, quote! { #(#blocks)* });
+    (j, quote! { { #tokens } })
+}
+
+// (dim, expression building the `ByteNgramHasher`) of `byte_ngrams(n = 4, stride = 1, max_bytes = 65536, dim = 1024, binary)`.
+fn byte_ngrams_handler(field: &syn::Field) -> Option<(usize, proc_macro2::TokenStream)> {
+    let nested = nested_handler(field, "byte_ngrams")?;
+    let mut dim = 37;
+    let mut n = 4;
+    let mut options = Vec::new();
+    for nm in nested {
+        match nm {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) => {
+                let option = mv.path.get_ident().map(|p| p.to_string()).unwrap_or_default();
+                let value = lit_number(&mv.lit) as usize;
+                match option.as_str() {
+                    "dim" => dim = value,
+                    "n" => n = value,
+                    "stride" if value == 0 => panic!("{}: byte_ngrams stride should be above 0", field_label(field)),
+                    "stride" => options.push(quote! { .stride(#value) }),
+                    "max_bytes" => options.push(quote! { .max_bytes(#value) }),
+                    _ => panic!("Unknown byte_ngrams option {:?}", option),
+                }
+            }
+            syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("binary") => options.push(quote! { .binary() }),
+            other => panic!("Unknown byte_ngrams option {:?}", other),
+        }
+    }
+    if n == 0 || dim == 0 {
+        panic!("{}: byte_ngrams need n > 0 and dim > 0", field_label(field));
+    }
+    Some((dim, quote! { ruiso::bytes::ByteNgramHasher::new(#n) #(#options)* }))
+}
+
+fn set_byte_ngrams_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    let (dim, hasher) = byte_ngrams_handler(field).unwrap();
+    let iplus = i + dim;
+    let fill = for_bytes(
+        name,
+        field,
+        quote! {
+            ngrams.fill(b, &mut slice[#i..#iplus]);
+        },
+    );
+    let tokens = quote! {
+        static NGRAMS: std::sync::OnceLock<ruiso::bytes::ByteNgramHasher> = std::sync::OnceLock::new();
+        let ngrams = NGRAMS.get_or_init(|| #hasher);
+        #fill
+    };
+    (iplus, quote! { { #tokens } })
+}
+
+// Patterns of `keywords` or `regex`, given as a list of strings or as a file with one per line
+// relative to the crate root. Blank lines and lines starting with `#` are skipped.
+// Gives (regex, patterns, file read).
+fn patterns_handler(field: &syn::Field) -> Option<(bool, Vec<String>, Option<String>)> {
+    let (regex, name) = if field_metas(field).iter().any(|m| m.path().is_ident("regex")) {
+        (true, "regex")
+    } else if field_metas(field).iter().any(|m| m.path().is_ident("keywords")) {
+        (false, "keywords")
+    } else {
+        return None;
+    };
+    let (patterns, file) = match name_value_handler(field, name) {
+        Some(syn::Lit::Str(path)) => {
+            let root = std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+            let path = std::path::Path::new(&root).join(path.value());
+            let text = match std::fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(e) => panic!("Can't read {} patterns from {}: {}", name, path.display(), e),
+            };
+            let patterns: Vec<String> = text
+                .lines()
+                .map(str::trim)
+                .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                .map(str::to_string)
+                .collect();
+            (patterns, Some(path.to_string_lossy().into_owned()))
+        }
+        Some(_) => panic!("{} should be a file name or a list of strings", name),
+        None => {
+            let patterns = list_handler(field, name)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|l| match l {
+                    syn::Lit::Str(v) => v.value(),
+                    _ => panic!("{} should be a list of strings", name),
+                })
+                .collect();
+            (patterns, None)
+        }
+    };
+    if patterns.is_empty() {
+        panic!("{}: no {} patterns", field_label(field), name);
+    }
+    if regex {
+        for p in &patterns {
+            if let Err(e) = Regex::new(p) {
+                panic!("Bad regex {:?}: {}", p, e);
+            }
+        }
+    }
+    Some((regex, patterns, file))
+}
+
+fn set_patterns_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    let (regex, patterns, file) = patterns_handler(field).unwrap();
+    let iplus = i + patterns.len();
+    let constructor = if regex {
+        quote! { ruiso::patterns::PatternMatcher::regex(vec![#(#patterns),*]) }
+    } else {
+        quote! { ruiso::patterns::PatternMatcher::keywords(vec![#(#patterns),*]) }
+    };
+    let count = match name_value_handler(field, "matches") {
+        Some(syn::Lit::Str(v)) if v.value() == "count" => quote! { .count() },
+        Some(syn::Lit::Str(v)) if v.value() == "presence" => quote! {},
+        None => quote! {},
+        Some(other) => panic!("matches should be \"presence\" or \"count\", found {:?}", other),
+    };
+    // Rebuilds the struct when the pattern file changes.
+    let track = file.map(|path| quote! { const _: &[u8] = include_bytes!(#path); });
+    let fill = for_each_string(
+        name,
+        field,
+        quote! {
+            patterns.fill(s, &mut slice[#i..#iplus]);
+        },
+    );
+    let tokens = quote! {
+        #track
+        static PATTERNS: std::sync::OnceLock<ruiso::patterns::PatternMatcher> = std::sync::OnceLock::new();
+        let patterns = PATTERNS.get_or_init(|| #constructor #count);
+        #fill
+    };
+    (iplus, quote! { { #tokens } })
+}
+
+// (dim, expression building the `PathFeaturizer`) of `path(extension = 16, dirs = 64, basename = 64)`,
+// with `extensions("exe", "dll")` for a vocabulary of extensions.
+fn path_handler(field: &syn::Field) -> Option<(usize, proc_macro2::TokenStream)> {
+    let nested = if detect_flag(field, "path") {
+        Vec::new()
+    } else {
+        nested_handler(field, "path")?
+    };
+    let (mut extension, mut dirs, mut basename) = (16, 64, 64);
+    let mut vocab: Option<Vec<String>> = None;
+    for n in nested {
+        match n {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) => {
+                let option = mv.path.get_ident().map(|p| p.to_string()).unwrap_or_default();
+                let value = lit_number(&mv.lit) as usize;
+                match option.as_str() {
+                    "extension" => extension = value,
+                    "dirs" => dirs = value,
+                    "basename" => basename = value,
+                    _ => panic!("Unknown path option {:?}", option),
+                }
+            }
+            syn::NestedMeta::Meta(syn::Meta::List(ml)) if ml.path.is_ident("extensions") => {
+                vocab = Some(
+                    ml.nested
+                        .iter()
+                        .map(|n| match n {
+                            syn::NestedMeta::Lit(syn::Lit::Str(v)) => v.value().trim_start_matches('.').to_lowercase(),
+                            _ => panic!("extensions should be strings"),
+                        })
+                        .collect(),
+                );
+            }
+            other => panic!("Unknown path option {:?}", other),
+        }
+    }
+    if extension == 0 || dirs == 0 || basename == 0 {
+        panic!("path blocks can't have 0 columns");
+    }
+    let featurizer = quote! { ruiso::path::PathFeaturizer::new(#extension, #dirs, #basename) };
+    let (extension, featurizer) = match vocab {
+        Some(tokens) => (
+            tokens.len() + 1,
+            quote! { #featurizer.extension_vocab(ruiso::vocab::Vocabulary::new(vec![#(#tokens),*])) },
+        ),
+        None => (extension, featurizer),
+    };
+    Some((PATH_WIDTH + extension + dirs + basename, featurizer))
+}
+
+// The length of `ruiso::path::PATH_NAMES`, the columns before the extension, checked by the generated code.
+const PATH_WIDTH: usize = 5;
+
+fn set_path_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    expect_single_string(field, "path");
+    let (dim, featurizer) = path_handler(field).unwrap();
+    let iplus = i + dim;
+    let fill = for_each_string(
+        name,
+        field,
+        quote! {
+            const _: () = assert!(ruiso::path::PATH_NAMES.len() == #PATH_WIDTH);
+            path.fill(s, &mut slice[#i..#iplus]);
+        },
+    );
+    let tokens = quote! {
+        static PATH: std::sync::OnceLock<ruiso::path::PathFeaturizer> = std::sync::OnceLock::new();
+        let path = PATH.get_or_init(|| #featurizer);
+        #fill
+    };
+    (iplus, quote! { { #tokens } })
+}
+
+// `string_stats` gives `Some(false)`, `string_stats(hash)` also hashes the string after the statistics.
+fn string_stats_handler(field: &syn::Field) -> Option<bool> {
+    if detect_flag(field, "string_stats") {
+        return Some(false);
+    }
+    nested_handler(field, "string_stats").map(|nested| match nested.as_slice() {
+        [syn::NestedMeta::Meta(syn::Meta::Path(p))] if p.is_ident("hash") => true,
+        _ => panic!("string_stats only takes hash, found {:?}", nested),
+    })
+}
+
+// The length of `ruiso::string_stats::STRING_STATS_NAMES`, checked by the generated code.
+const STRING_STATS_WIDTH: usize = 8;
+
+fn set_string_stats_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    expect_single_string(field, "string_stats");
+    let iplus = i + STRING_STATS_WIDTH;
+    let stats = for_each_string(
+        name,
+        field,
+        quote! {
+            const _: () = assert!(ruiso::string_stats::STRING_STATS_NAMES.len() == #STRING_STATS_WIDTH);
+            ruiso::string_stats::fill_string_stats(s, &mut slice[#i..#iplus]);
+        },
+    );
+    if string_stats_handler(field).unwrap() {
+        let (iplus, hashed) = set_string_field(name, iplus, field);
+        (iplus, quote! { { #stats } { #hashed } })
+    } else {
+        (iplus, quote! { { #stats } })
+    }
+}
+
+// (dim, expression building the `TextHasher`) of `text(tokenizer = "words", dim = 512, lowercase, ...)`.
+fn text_handler(field: &syn::Field) -> Option<(usize, proc_macro2::TokenStream)> {
+    let nested = if detect_flag(field, "text") {
+        Vec::new()
+    } else {
+        nested_handler(field, "text")?
+    };
+    let (hasher, rest) = text_options(nested);
+    let mut dim = 37;
+    for n in rest {
+        match n {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) if mv.path.is_ident("dim") => {
+                dim = lit_number(&mv.lit) as usize;
+            }
+            other => panic!("Unknown text option {:?}", other),
+        }
+    }
+    Some((dim, hasher))
+}
+
+// Builds a `TextHasher` from the tokenizer and cleanup options, returning the options it doesn't know.
+fn text_options(nested: Vec<syn::NestedMeta>) -> (proc_macro2::TokenStream, Vec<syn::NestedMeta>) {
+    let mut tokenizer = None;
+    let mut pattern = None;
+    let mut options = Vec::new();
+    let mut rest = Vec::new();
+    for n in nested {
+        match &n {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) => {
+                let option = mv.path.get_ident().map(|p| p.to_string()).unwrap_or_default();
+                match (option.as_str(), &mv.lit) {
+                    ("tokenizer", syn::Lit::Str(v)) => tokenizer = Some(v.value()),
+                    ("pattern", syn::Lit::Str(v)) => pattern = Some(v.value()),
+                    ("min_len", lit) => {
+                        let n = lit_number(lit) as usize;
+                        options.push(quote! { .min_len(#n) });
+                    }
+                    ("max_tokens", lit) => {
+                        let n = lit_number(lit) as usize;
+                        options.push(quote! { .max_tokens(#n) });
+                    }
+                    _ => rest.push(n),
+                }
+            }
+            syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("lowercase") => {
+                options.push(quote! { .lowercase() });
+            }
+            syn::NestedMeta::Meta(syn::Meta::List(ml)) if ml.path.is_ident("stopwords") => {
+                let words: Vec<String> = ml
+                    .nested
+                    .iter()
+                    .map(|n| match n {
+                        syn::NestedMeta::Lit(syn::Lit::Str(v)) => v.value(),
+                        _ => panic!("stopwords should be strings"),
+                    })
+                    .collect();
+                options.push(quote! { .stopwords([#(#words),*].iter().copied()) });
+            }
+            _ => rest.push(n),
+        }
+    }
+    let tokenizer = tokenizer_tokens(tokenizer, pattern);
+    (quote! { ruiso::text::TextHasher::new(#tokenizer)#(#options)* }, rest)
+}
+
+enum TfIdfOption {
+    Hashed(usize),
+    Vocab { top_k: usize, min_df: usize },
+}
+
+// (columns, sublinear, l2, expression building the `TextHasher`) of `tfidf(dim = 1024, sublinear, l2, ...)`.
+fn tfidf_handler(field: &syn::Field) -> Option<(TfIdfOption, bool, bool, proc_macro2::TokenStream)> {
+    let (hasher, rest) = text_options(nested_handler(field, "tfidf")?);
+    let mut dim = None;
+    let mut top_k = None;
+    let mut min_df = 1;
+    let mut sublinear = false;
+    let mut l2 = false;
+    for n in rest {
+        match n {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) if mv.path.is_ident("dim") => {
+                dim = Some(lit_number(&mv.lit) as usize);
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) if mv.path.is_ident("top_k") => {
+                top_k = Some(lit_number(&mv.lit) as usize);
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) if mv.path.is_ident("min_df") => {
+                min_df = lit_number(&mv.lit) as usize;
+            }
+            syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("sublinear") => sublinear = true,
+            syn::NestedMeta::Meta(syn::Meta::Path(p)) if p.is_ident("l2") => l2 = true,
+            other => panic!("Unknown tfidf option {:?}", other),
+        }
+    }
+    let columns = match (dim, top_k) {
+        (Some(dim), None) => TfIdfOption::Hashed(dim),
+        (None, Some(top_k)) => TfIdfOption::Vocab { top_k, min_df },
+        (None, None) => TfIdfOption::Hashed(37),
+        (Some(_), Some(_)) => panic!("Pick one of dim and top_k for tfidf on {}", field_label(field)),
+    };
+    Some((columns, sublinear, l2, hasher))
+}
+
+fn tfidf_width(field: &syn::Field) -> usize {
+    match tfidf_handler(field).unwrap().0 {
+        TfIdfOption::Hashed(dim) => dim,
+        TfIdfOption::Vocab { top_k, .. } => top_k,
+    }
+}
+
+// Collects the tokens of a text field into `doc`, with its own `TextHasher`.
+fn tfidf_doc(name: &syn::Ident, field: &syn::Field) -> proc_macro2::TokenStream {
+    let hasher = tfidf_handler(field).unwrap().3;
+    let collect = for_each_string(
+        name,
+        field,
+        quote! {
+            doc.extend(text.tokens(s).map(|t| t.into_owned()));
+        },
+    );
+    quote! {
+        static TEXT: std::sync::OnceLock<ruiso::text::TextHasher> = std::sync::OnceLock::new();
+        let text = TEXT.get_or_init(|| #hasher);
+        let mut doc: Vec<String> = Vec::new();
+        #collect
     }
+}
+
+fn set_tfidf_field(name:&syn::Ident,i: usize, k: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    let iplus = i + tfidf_width(field);
+    let doc = tfidf_doc(name, field);
+    let tokens = quote! {
+        {
+            #doc
+            fitted.tfidf(#k).fill(&doc, &mut slice[#i..#iplus]);
+        }
+    };
     (iplus, tokens)
 }
 
-fn set_bool_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
-    let iplus = i + 1;
-    let field_name = &field.ident;
-    let tokens;
-    if detect_optional(field) {
-        match default_field_handler(&field) {
-            Some(f) => {
-                tokens = quote! {
-                    if let Some(x) = #name.#field_name {
-                        if x {
-                            slice[#i] = 1.0;
-                        }
-                    } else {
-                        slice[#i] = #f;
-                    }
-                };
-            }
-            None => {
-                tokens = quote! {
-                    if let Some(x) = #name.#field_name {
-                        if x {
-                            slice[#i] = 1.0;
-                        }
-                    }
-                };
+fn tokenizer_tokens(tokenizer: Option<String>, pattern: Option<String>) -> proc_macro2::TokenStream {
+    match (tokenizer.as_deref(), pattern) {
+        (None, None) | (Some("whitespace"), None) => quote! { ruiso::text::Tokenizer::Whitespace },
+        (Some("words"), None) => quote! { ruiso::text::Tokenizer::Words },
+        (None, Some(p)) | (Some("regex"), Some(p)) => {
+            if let Err(e) = Regex::new(&p) {
+                panic!("Bad tokenizer pattern {:?}: {}", p, e);
             }
+            quote! { ruiso::text::Tokenizer::regex(#p) }
         }
-    } else {
-        tokens = quote! {
-            if &#name.#field_name {
-                slice[#i] = 1.0;
-            }
-        };
+        (Some("regex"), None) => panic!("The regex tokenizer needs a pattern"),
+        (Some(other), _) => panic!("Unknown tokenizer {:?}, use \"whitespace\", \"words\" or \"regex\"", other),
     }
-    (iplus, tokens)
 }
 
-fn string_dimension_handler(field: &syn::Field) -> Option<syn::LitInt> {
-    if let Some(attr) = field.attrs.first() {
-        let meta = match attr.parse_meta().unwrap() {
-            syn::Meta::List(ml) => match &ml.nested[0] {
-                syn::NestedMeta::Meta(m) => m.clone(),
-                _ => panic!("meta is not a list"),
-            },
-            _ => panic!("meta is not a list"),
-        };
-        if let syn::Meta::NameValue(mv) = meta {
-            if mv.path.is_ident("dim") {
-                if let syn::Lit::Int(v) = mv.lit {
-                    Some(v)
-                } else {
-                    None
+// (dim, expression building the `NgramHasher`) of `ngrams(chars, min_n = 3, max_n = 5, dim = 1024, ...)`.
+fn ngrams_handler(field: &syn::Field) -> Option<(usize, proc_macro2::TokenStream)> {
+    let nested = nested_handler(field, "ngrams")?;
+    let mut dim = 37;
+    let mut words = false;
+    let mut min_n = None;
+    let mut max_n = None;
+    let mut tokenizer = None;
+    let mut pattern = None;
+    let mut options = Vec::new();
+    for n in nested {
+        match n {
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) => {
+                let option = mv.path.get_ident().map(|p| p.to_string()).unwrap_or_default();
+                match (option.as_str(), &mv.lit) {
+                    ("tokenizer", syn::Lit::Str(v)) => tokenizer = Some(v.value()),
+                    ("pattern", syn::Lit::Str(v)) => pattern = Some(v.value()),
+                    ("dim", lit) => dim = lit_number(lit) as usize,
+                    ("n", lit) => {
+                        min_n = Some(lit_number(lit) as usize);
+                        max_n = min_n;
+                    }
+                    ("min_n", lit) => min_n = Some(lit_number(lit) as usize),
+                    ("max_n", lit) => max_n = Some(lit_number(lit) as usize),
+                    _ => panic!("Unknown ngrams option {:?}", mv),
                 }
-            } else {
-                None
             }
-        } else {
-            None
+            syn::NestedMeta::Meta(syn::Meta::Path(p)) => match p.get_ident().map(|p| p.to_string()).as_deref() {
+                Some("chars") => words = false,
+                Some("words") => words = true,
+                Some("boundary") => options.push(quote! { .boundary() }),
+                Some("lowercase") => options.push(quote! { .lowercase() }),
+                Some("binary") => options.push(quote! { .binary() }),
+                _ => panic!("Unknown ngrams option {:?}", p.get_ident()),
+            },
+            other => panic!("Unknown ngrams option {:?}", other),
         }
-    } else {
-        None
     }
+    let (min_n, max_n) = match (min_n, max_n) {
+        (Some(lo), Some(hi)) => (lo, hi),
+        (Some(n), None) | (None, Some(n)) => (n, n),
+        (None, None) if words => (2, 2),
+        (None, None) => (3, 3),
+    };
+    if min_n == 0 || min_n > max_n {
+        panic!("ngrams need 0 < min_n <= max_n, found {} and {}", min_n, max_n);
+    }
+    let hasher = if words {
+        let tokenizer = tokenizer_tokens(tokenizer, pattern);
+        quote! { ruiso::ngram::NgramHasher::words(#tokenizer, #min_n, #max_n) }
+    } else {
+        if tokenizer.is_some() || pattern.is_some() {
+            panic!("Only word ngrams take a tokenizer");
+        }
+        quote! { ruiso::ngram::NgramHasher::chars(#min_n, #max_n) }
+    };
+    Some((dim, quote! { #hasher #(#options)* }))
 }
 
-fn set_string_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
-    let dim: usize = match string_dimension_handler(field) {
-        Some(d) => d.base10_parse().unwrap(),
-        None => 37,
-    };
+fn set_ngrams_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    let (dim, hasher) = ngrams_handler(field).unwrap();
     let iplus = i + dim;
-    let field_name = &field.ident;
+    let fill = for_each_string(
+        name,
+        field,
+        quote! {
+            ngrams.fill(s, &mut slice[#i..#iplus]);
+        },
+    );
+    let tokens = quote! {
+        static NGRAMS: std::sync::OnceLock<ruiso::ngram::NgramHasher> = std::sync::OnceLock::new();
+        let ngrams = NGRAMS.get_or_init(|| #hasher);
+        #fill
+    };
+    (iplus, quote! { { #tokens } })
+}
 
-    let tokens;
-    if detect_optional(field) {
-        tokens = quote! {
-            if let Some(x) = &#name.#field_name {
-                let mut hasher = std::collections::hash_map::DefaultHasher::new();
-                x.hash(&mut hasher);
-                let result = (hasher.finish() as usize) % #dim;
-                slice[(#i)+result] += 1.0;
-            }
-        };
-    } else {
-        tokens = quote! {
-            let mut hasher = std::collections::hash_map::DefaultHasher::new();
-            #name.#field_name.hash(&mut hasher);
-            let result = (hasher.finish() as usize) % #dim;
-            slice[(#i)+result] += 1.0;
-        };
-    }
-    (iplus, tokens)
+fn set_text_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    let (dim, hasher) = text_handler(field).unwrap();
+    let iplus = i + dim;
+    let fill = for_each_string(
+        name,
+        field,
+        quote! {
+            text.fill(s, &mut slice[#i..#iplus]);
+        },
+    );
+    let tokens = quote! {
+        static TEXT: std::sync::OnceLock<ruiso::text::TextHasher> = std::sync::OnceLock::new();
+        let text = TEXT.get_or_init(|| #hasher);
+        #fill
+    };
+    (iplus, quote! { { #tokens } })
 }
 
 fn set_vec_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
@@ -290,45 +1848,189 @@ fn set_vec_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_
         if let syn::PathArguments::AngleBracketed(pat) =
             &pat.path.segments.last().unwrap().arguments
         {
-            if let syn::GenericArgument::Type(pat) = pat.args.last().unwrap() {
-                if let syn::Type::Path(pat) = pat {
-                    if pat.path.is_ident("String") {
-                        if detect_optional(field) {
-                            tokens = quote! {
-                                if let Some(x) = &#name.#field_name {
-                                    for s in x {
-                                        let mut hasher = std::collections::hash_map::DefaultHasher::new();
-                                        s.hash(&mut hasher);
-                                        let result = (hasher.finish() as usize) % #dim;
-                                        slice[(#i)+result] += 1.0;
-                                    }
-                                }
-                            };
-                        } else {
-                            tokens = quote! {
-                                for s in &#name.#field_name {
+            if let syn::GenericArgument::Type(syn::Type::Path(pat)) = pat.args.last().unwrap() {
+                if pat.path.is_ident("String") {
+                    if detect_optional(field) {
+                        tokens = quote! {
+                            if let Some(x) = &#name.#field_name {
+                                for s in x {
                                     let mut hasher = std::collections::hash_map::DefaultHasher::new();
                                     s.hash(&mut hasher);
                                     let result = (hasher.finish() as usize) % #dim;
                                     slice[(#i)+result] += 1.0;
                                 }
-                            };
-                        }
+                            }
+                        };
                     } else {
-                        panic!("{:?}",);
+                        tokens = quote! {
+                            for s in &#name.#field_name {
+                                let mut hasher = std::collections::hash_map::DefaultHasher::new();
+                                s.hash(&mut hasher);
+                                let result = (hasher.finish() as usize) % #dim;
+                                slice[(#i)+result] += 1.0;
+                            }
+                        };
                     }
                 } else {
-                    panic!("{:?}",);
+                    panic!("{:?}", field.ty);
                 }
             } else {
-                panic!("{:?}",);
+                panic!("{:?}", field.ty);
             }
         } else {
-            panic!("{:?}",);
+            panic!("{:?}", field.ty);
+        }
+    } else {
+        panic!("{:?}", field.ty);
+    }
+    (iplus, tokens)
+}
+
+fn combine_handler(field: &syn::Field) -> String {
+    match name_value_handler(field, "combine") {
+        Some(syn::Lit::Str(v)) => match v.value().as_str() {
+            "sum" | "max_abs" => v.value(),
+            "max" => panic!("combine = \"max\" is now \"max_abs\", which keeps the weight with the largest magnitude"),
+            other => panic!("combine should be \"sum\" or \"max_abs\", not {:?}", other),
+        },
+        Some(_) => panic!("combine should be a string"),
+        None => "sum".to_string(),
+    }
+}
+
+fn detect_weighted_pairs(field: &syn::Field) -> bool {
+    if let Type::Path(pat) = get_underlying_type_option(&field.ty) {
+        if let syn::PathArguments::AngleBracketed(pat) = &pat.path.segments.last().unwrap().arguments {
+            if let Some(syn::GenericArgument::Type(syn::Type::Tuple(tup))) = pat.args.last() {
+                return tup.elems.len() == 2;
+            }
+        }
+    }
+    false
+}
+
+fn set_weighted_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    let dim: usize = match string_dimension_handler(field) {
+        Some(d) => d.base10_parse().unwrap(),
+        None => 37,
+    };
+    let iplus = i + dim;
+    let field_name = &field.ident;
+
+    let signed = if detect_flag(field, "signed") {
+        quote! {
+            let w = if (h >> 63) == 1 { -w } else { w };
+        }
+    } else {
+        quote! {}
+    };
+    // Ties in magnitude go to the positive weight, so the result doesn't depend on iteration order.
+    let combine = if combine_handler(field) == "max_abs" {
+        quote! {
+            let old = slice[(#i)+result];
+            if w.abs() > old.abs() || (w.abs() == old.abs() && w > old) {
+                slice[(#i)+result] = w;
+            }
+        }
+    } else {
+        quote! {
+            slice[(#i)+result] += w;
+        }
+    };
+    let setter = quote! {
+        for (s, w) in x {
+            let w = *w as f32;
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            s.hash(&mut hasher);
+            let h = hasher.finish();
+            let result = (h as usize) % #dim;
+            #signed
+            #combine
+        }
+    };
+    let tokens = if detect_optional(field) {
+        quote! {
+            if let Some(x) = &#name.#field_name {
+                #setter
+            }
         }
     } else {
-        panic!("{:?}",);
+        quote! {
+            let x = &#name.#field_name;
+            #setter
+        }
+    };
+    (iplus, tokens)
+}
+
+fn set_time_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+    let field_name = &field.ident;
+    let mut components: Vec<proc_macro2::TokenStream> = Vec::new();
+    // Epoch seconds take the three columns of `ruiso::numeric::split_int`.
+    let mut width = 0;
+    let mut age = false;
+    let mut utc_offset: i64 = 0;
+    let mut reference = None;
+    for nested in nested_handler(field, "time").unwrap_or_default() {
+        match nested {
+            syn::NestedMeta::Meta(syn::Meta::Path(p)) => {
+                let component = match p.get_ident().map(|c| c.to_string()).as_deref() {
+                    Some("hour") => quote! { Hour },
+                    Some("weekday") => quote! { Weekday },
+                    Some("month") => quote! { Month },
+                    Some("day") => quote! { DayOfMonth },
+                    Some("weekend") => quote! { IsWeekend },
+                    Some("epoch") => quote! { EpochSeconds },
+                    Some("age") => {
+                        age = true;
+                        quote! { Age }
+                    }
+                    _ => panic!("Unknown time component {:?}", p.get_ident()),
+                };
+                width += if p.is_ident("epoch") { 3 } else { 1 };
+                components.push(quote! { ruiso::time::TimeComponent::#component });
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) if mv.path.is_ident("utc_offset") => {
+                utc_offset = lit_number(&mv.lit) as i64;
+            }
+            syn::NestedMeta::Meta(syn::Meta::NameValue(mv)) if mv.path.is_ident("reference") => {
+                reference = Some(lit_number(&mv.lit));
+            }
+            other => panic!("Unknown time option {:?}", other),
+        }
     }
+    let reference = match reference {
+        Some(r) => quote! { Some(#r) },
+        None if age => panic!("{}: age needs a reference time, like reference = 1600000000", field_label(field)),
+        None => quote! { None },
+    };
+    let (dim, components) = if components.is_empty() {
+        (5, quote! { ruiso::time::DEFAULT_COMPONENTS })
+    } else {
+        (width, quote! { &[#(#components),*] })
+    };
+    let iplus = i + dim;
+    let setter = |x: proc_macro2::TokenStream| {
+        quote! {
+            ruiso::time::fill_time(
+                ruiso::time::Timestamp::epoch_seconds(#x),
+                #components,
+                #utc_offset,
+                #reference,
+                &mut slice[#i..#iplus],
+            );
+        }
+    };
+    let tokens = if detect_optional(field) {
+        let set = setter(quote! { x });
+        quote! {
+            if let Some(x) = &#name.#field_name {
+                #set
+            }
+        }
+    } else {
+        setter(quote! { &#name.#field_name })
+    };
     (iplus, tokens)
 }
 
@@ -341,108 +2043,160 @@ fn set_custom_field(
 ) -> (usize, proc_macro2::TokenStream) {
     let iplus = i + dimension as usize;
     let field_name = &field.ident;
-    let tokens;
-    if detect_optional(field) {
-        tokens = quote! {
+    let tokens = if detect_optional(field) {
+        // Spelled out so featurizers generic over the value, like embeddings, know which default to use.
+        let f_type = get_underlying_type_option(&field.ty);
+        quote! {
             if let Some(x) = &#name.#field_name {
                 #featurizer::fill_slice(x,&mut slice[#i..#iplus]);
             } else {
-                #featurizer::default(&mut slice[#i..#iplus]);
+                <#featurizer as Featurizer<#f_type>>::default(&mut slice[#i..#iplus]);
             }
-        };
+        }
     } else {
-        tokens = quote! {
+        quote! {
             #featurizer::fill_slice(&#name.#field_name,&mut slice[#i..#iplus]);
-        };
+        }
+    };
+    (iplus, tokens)
+}
+
+// `multi_hot` keeps the largest value of each column over the elements, `multi_hot = "count"` sums them.
+fn multi_hot_handler(field: &syn::Field) -> Option<bool> {
+    if detect_flag(field, "multi_hot") {
+        return Some(false);
+    }
+    match name_value_handler(field, "multi_hot") {
+        Some(syn::Lit::Str(v)) => match v.value().as_str() {
+            "set" => Some(false),
+            "count" => Some(true),
+            other => panic!("multi_hot should be \"set\" or \"count\", not {:?}", other),
+        },
+        Some(_) => panic!("multi_hot should be a string"),
+        None => None,
     }
+}
+
+fn set_multi_hot_field(
+    name:&syn::Ident,
+    i: usize,
+    field: &syn::Field,
+    featurizer: syn::Ident,
+    dimension: u16,
+) -> (usize, proc_macro2::TokenStream) {
+    let dim = dimension as usize;
+    let iplus = i + dim;
+    let field_name = &field.ident;
+    let combine = if multi_hot_handler(field).unwrap() {
+        quote! { *s += *v; }
+    } else {
+        quote! { *s = s.max(*v); }
+    };
+    let setter = quote! {
+        for e in x {
+            let mut one = [0f32; #dim];
+            #featurizer::fill_slice(e, &mut one);
+            for (s, v) in slice[#i..#iplus].iter_mut().zip(one.iter()) {
+                #combine
+            }
+        }
+    };
+    let tokens = if detect_optional(field) {
+        quote! {
+            if let Some(x) = &#name.#field_name {
+                #setter
+            }
+        }
+    } else {
+        quote! {
+            let x = &#name.#field_name;
+            #setter
+        }
+    };
     (iplus, tokens)
 }
 
 fn custom_featurizer_handler(field: &syn::Field) -> Option<(syn::Ident, u16)> {
-    if let Some(attr) = field.attrs.first() {
-        let meta = match attr.parse_meta().unwrap() {
-            syn::Meta::List(ml) => match &ml.nested[0] {
-                syn::NestedMeta::Meta(m) => m.clone(),
-                _ => panic!("meta is not a list"),
-            },
-            _ => panic!("meta is not a list"),
+    if let Some(syn::Lit::Str(v)) = name_value_handler(field, "featurizer") {
+        let re = Regex::new(r"[[:alpha:]]*([0-9]*)").unwrap();
+        let dimension: u16 = match re.captures(&v.value()).unwrap().get(1) {
+            Some(m) => m.as_str().parse().unwrap(),
+            None => panic!("Featurizers need to end in their dimension."),
         };
-        if let syn::Meta::NameValue(mv) = meta {
-            if mv.path.is_ident("featurizer") {
-                if let syn::Lit::Str(v) = mv.lit {
-                    let re = Regex::new(r"[[:alpha:]]*([0-9]*)").unwrap();
-                    let dimension: u16 = match re.captures(&v.value()).unwrap().get(1) {
-                        Some(m) => m.as_str().parse().unwrap(),
-                        None => panic!("Featurizers need to end in their dimension."),
-                    };
-                    let ident = Ident::new(&v.value(), Span::call_site());
-                    Some((ident, dimension))
-                } else {
-                    None
-                }
-            } else {
-                None
-            }
-        } else {
-            None
-        }
+        let ident = Ident::new(&v.value(), Span::call_site());
+        Some((ident, dimension))
     } else {
         None
     }
 }
 
-fn set_value_field(name:&syn::Ident,i: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
+fn set_value_field(name:&syn::Ident,i: usize, k: usize, field: &syn::Field) -> (usize, proc_macro2::TokenStream) {
     match custom_featurizer_handler(field) {
+        Some((custom, len)) if multi_hot_handler(field).is_some() => set_multi_hot_field(name, i, field, custom, len),
         Some((custom, len)) => set_custom_field(name,i, field, custom, len),
+        None if flags_handler(field).is_some() => set_flags_field(name, i, field),
+        None if vocab_handler(field).is_some() => set_vocab_field(name, i, k, field),
+        None if path_handler(field).is_some() => set_path_field(name, i, field),
+        None if patterns_handler(field).is_some() => set_patterns_field(name, i, field),
+        None if bytes_handler(field).is_some() => set_bytes_field(name, i, field),
+        None if byte_ngrams_handler(field).is_some() => set_byte_ngrams_field(name, i, field),
+        None if string_stats_handler(field).is_some() => set_string_stats_field(name, i, field),
+        None if text_handler(field).is_some() => set_text_field(name, i, field),
+        None if tfidf_handler(field).is_some() => set_tfidf_field(name, i, k, field),
+        None if ngrams_handler(field).is_some() => set_ngrams_field(name, i, field),
+        None if target_handler(field).is_some() => set_encoded_field(name, i, k, field),
+        None if count_encoding_handler(field).is_some() => set_encoded_field(name, i, k, field),
         None => {
             if let Type::Path(pat) = &get_underlying_type_option(&field.ty) {
-                match &pat.path.segments.last().unwrap().ident.to_string().as_str() {
-                    &"f32" => set_basic_field(name,i, field),
-                    &"f64" => set_basic_field(name,i, field),
-                    &"u8" => set_basic_field(name,i, field),
-                    &"u16" => set_basic_field(name,i, field),
-                    &"u32" => set_basic_field(name,i, field),
-                    &"u64" => set_basic_field(name,i, field),
-                    &"i8" => set_basic_field(name,i, field),
-                    &"i16" => set_basic_field(name,i, field),
-                    &"i32" => set_basic_field(name,i, field),
-                    &"i64" => set_basic_field(name,i, field),
-                    &"usize" => set_basic_field(name,i, field),
-                    &"bool" => set_bool_field(name,i, field),
-                    &"String" => set_string_field(name,i, field),
-                    &"Vec" => set_vec_field(name,i, field),
+                match pat.path.segments.last().unwrap().ident.to_string().as_str() {
+                    "f32" => set_basic_field(name,i, k, field),
+                    "f64" => set_basic_field(name,i, k, field),
+                    "u8" => set_basic_field(name,i, k, field),
+                    "u16" => set_basic_field(name,i, k, field),
+                    "u32" => set_basic_field(name,i, k, field),
+                    "u64" => set_basic_field(name,i, k, field),
+                    "i8" => set_basic_field(name,i, k, field),
+                    "i16" => set_basic_field(name,i, k, field),
+                    "i32" => set_basic_field(name,i, k, field),
+                    "i64" => set_basic_field(name,i, k, field),
+                    "usize" => set_basic_field(name,i, k, field),
+                    "bool" => set_bool_field(name,i, field),
+                    "String" => set_string_field(name,i, field),
+                    "Vec" if detect_weighted_pairs(field) => set_weighted_field(name,i, field),
+                    "Vec" => set_vec_field(name,i, field),
+                    "SystemTime" => set_time_field(name,i, field),
+                    "DateTime" => set_time_field(name,i, field),
+                    "NaiveDateTime" => set_time_field(name,i, field),
+                    "HashMap" => set_weighted_field(name,i, field),
+                    "BTreeMap" => set_weighted_field(name,i, field),
                     _ => panic!("This field should have a custom featurizer provided"),
                 }
             } else {
-                panic!("{:?}",);
+                panic!("{:?}", field.ty);
             }
         }
     }
 }
 
 fn detect_off(field: &syn::Field) -> bool {
-    if let Some(attr) = field.attrs.first() {
-        let meta = match attr.parse_meta().unwrap() {
-            syn::Meta::List(ml) => match &ml.nested[0] {
-                syn::NestedMeta::Meta(m) => m.clone(),
-                _ => panic!("meta is not a list"),
-            },
-            _ => panic!("meta is not a list"),
-        };
-        if let syn::Meta::Path(pat) = meta {
-            if pat.is_ident("off") {
-                return true;
-            }
-        }
-    }
-    false
+    detect_flag(field, "off")
 }
 
 /// # Struct Featurization
 /// Gives the struct a  
 /// For example:
-/// ```rust
-/// ---
+/// ```
+/// use ruiso::*;
+/// use std::collections::HashMap;
+///
+/// #[derive(EnumFeature)]
+/// pub enum ExampleEnum {
+///     Foo,
+///     Bar,
+///     Kal,
+///     Ell,
+/// }
+///
 /// #[derive(StructFeature)]
 /// pub struct TestStruct {
 ///     foo: u32,
@@ -454,13 +2208,92 @@ fn detect_off(field: &syn::Field) -> bool {
 ///     ell: ExampleEnum,
 ///     #[struct_feature(dim = 21)]
 ///     kan: String,
+///     #[struct_feature(dim = 16, combine = "max_abs", signed)]
+///     terms: HashMap<String, f32>,
 /// }
+/// # assert!(TestStructFeaturizer43::dim() == 43);
 /// ```
-/// produces TestStructFeaturization27 and enables the trait Featurizable for your struct. 
+/// produces TestStructFeaturizer43 and enables the trait Featurizable for your struct. 
+/// `TestStruct::feature_names()` names every column.
 /// For nesting use the featurizer decoration with the name of the featurizer you want to use.
-/// For strings we can specify the dimension of the hashing trick we want to use.
 /// We can also turn off fields we don't want to include.
+/// Fitted options, marked fitted below, are fitted by the generated `fit` and installed with `install_fitted`
+/// or `load_fitted` before featurizing.
+///
+/// ## Numeric fields
 /// For single value fields (u8,f32,i64, etc..) we can give a default value if they are optional.
+/// Numeric fields can be transformed with `transform = "clip(0, 1e6) | log1p"`, `scale = 0.001` and `offset = 1.0`,
+/// applied in the order they are written. The transforms are `log1p`, `signed_log`, `sqrt`, `clip(min, max)`,
+/// `scale(factor)`, `offset(by)` and `minmax(min, max)`, see `ruiso::transform`.
+/// Numeric fields marked `scale = "standard"` are written as `(x - mean) / std`, with the statistics
+/// fitted on the transformed values.
+/// NaN and infinities are written as they are, unless `non_finite` is set to `"zero"`, `"default"` (the field's default, or zero),
+/// `"clamp"` (NaN to zero, infinities to the largest `f32`) or `"error"` (panic), applied after the transforms and
+/// before bucketing or the cyclic encoding. Put it on the struct to cover every field. Integer encodings, `bits`
+/// and `int_encoding`, are always finite and reject it.
+/// Large integers can be written exactly as three 24 bit parts, high first, with `int_encoding = "split"`,
+/// or as their signed log magnitude with `int_encoding = "log"`.
+/// Periodic values can be written as the sine and cosine of `2πx/period` with `cyclic = 24`, taking 2 columns.
+/// A missing optional value leaves both at zero, unless a default is given.
+/// Timestamps, `SystemTime` or with the `chrono` feature `DateTime` and `NaiveDateTime`, are expanded into
+/// hour, weekday, month, day of month and is-weekend columns. Pick other columns with
+/// `time(hour, weekday, month, day, weekend, epoch, age, utc_offset = 3600, reference = 1600000000)`,
+/// where epoch writes the whole seconds exactly in three columns, like `int_encoding = "split"`, and age is
+/// measured to the reference epoch seconds, which it needs.
+/// Unsigned flag words can be split into one column per bit, lowest bit first, with `bits = 8`, up to the width
+/// of the field's type. With the `bitflags` feature, `flags = 4` writes one column per named flag of a `bitflags`
+/// type, named `field:FLAG` in `feature_names`. The number of flags is checked against the type when compiling.
+/// Numeric fields can also be one hot encoded into bins, with `buckets = [0, 10, 100]` giving 4 columns,
+/// or `quantiles = 4` giving 4 columns with fitted boundaries.
+///
+/// ## Categorical fields
+/// Strings, and `Vec<String>` as counts, can be one hot encoded against a vocabulary with
+/// `vocab = ["tcp", "udp", "icmp"]`, or against the `top_k` most frequent strings seen at least `min_count` times
+/// with the fitted `vocab(top_k = 100, min_count = 5)`. The last column counts strings out of the vocabulary.
+/// Collections of values with a featurizer, like `Vec<Animals>` or `HashSet<Animals>` for an enum deriving
+/// `EnumFeature`, are encoded element by element with `featurizer = "AnimalsFeaturizer4", multi_hot`,
+/// giving a multi-hot vector over the variants, or with `multi_hot = "count"` to count each variant.
+/// High cardinality categories, `String` or any `Hash` type, can be replaced by their smoothed mean label with
+/// `target(prior_weight = 10, min_count = 1)`, taking 1 column. These need labels, fitted with `fit_labeled`,
+/// and `featurize_out_of_fold` featurizes the training rows without leaking their label.
+/// They can also be replaced by how often they were seen with `count_encoding`, or their share of the rows with
+/// `count_encoding(frequency)`. Add `log` to write `ln(1 + x)` and `unseen = -1` to set the value of unseen categories.
+///
+/// ## Text fields
+/// For strings we can specify the dimension of the hashing trick we want to use.
+/// Weighted tokens, `HashMap<String, f32>` or `Vec<(String, f32)>`, are hashed the same way but add their weight.
+/// Colliding weights are summed unless `combine = "max_abs"` is given, which keeps the weight with the largest magnitude,
+/// the positive one on a tie, and `signed` flips the sign of the weight with a bit of the token's hash.
+/// `string_stats` replaces the hashing of a single string, `String` or `&str`, with its length, entropy, ratios of ASCII digits, uppercase,
+/// punctuation and non-ASCII characters, longest consonant run and number of distinct characters, 8 columns in all,
+/// named `field:length` and so on, and `string_stats(hash)` writes them before the usual hashed block.
+/// See `ruiso::string_stats`.
+/// Single file paths, `String`, `&str` or `PathBuf`, are split up with `path` instead of hashed whole: the depth, a hidden file flag,
+/// flags for `/tmp`, `/dev/shm` and home directories, then hashed blocks for the extension, the directories and the
+/// file name, sized with `path(extension = 16, dirs = 64, basename = 64)`. `extensions("exe", "dll")` gives the
+/// extension one column each plus one for the others. Columns are named `field:depth`, `field:ext[j]` and so on,
+/// see `ruiso::path::PathFeaturizer::names`.
+/// Strings can be matched against lists of patterns, one column per pattern, with `keywords = ["mimikatz", "-enc"]`
+/// for substrings or `regex = ["^powershell", "base64"]` for regexes. Either can instead name a file, relative to the
+/// crate root, with one pattern per line: `keywords = "keywords.txt"`. Columns mark the patterns found, or count their
+/// matches with `matches = "count"`, and are named `field:pattern` in `feature_names`.
+/// Text can be split into tokens that are hashed one by one with
+/// `text(tokenizer = "words", dim = 512, lowercase, min_len = 2, max_tokens = 100, stopwords("the", "a"))`,
+/// where the tokenizer is `"whitespace"`, the default, `"words"` for unicode words or `"regex"` with `pattern = "[a-z]+"`.
+/// See `ruiso::text::TextHasher`.
+/// Text can also be weighed by TF-IDF, fitted, with `tfidf(dim = 1024, sublinear, l2)`
+/// over hashed buckets or `tfidf(top_k = 5000, min_df = 2)` over the tokens found in the most documents.
+/// It takes the same tokenizer options as `text`. See `ruiso::tfidf::TfIdf`.
+/// Character n-grams are hashed with `ngrams(chars, min_n = 3, max_n = 5, dim = 1024)` and word n-grams with
+/// `ngrams(words, n = 2, tokenizer = "words")`. `boundary` wraps the text in start and end markers, `lowercase`
+/// lowercases it and `binary` marks n-grams instead of counting them. See `ruiso::ngram::NgramHasher`.
+///
+/// ## Binary fields
+/// Binary data, `Vec<u8>` or `&[u8]`, is featurized with `bytes`, writing a 256 column normalized byte histogram,
+/// the 256 column byte-entropy histogram and the length. Pick some of them with `bytes(histogram, entropy, length)`
+/// and set the entropy windows with `window = 2048, step = 1024`. See `ruiso::bytes`.
+/// `byte_ngrams(n = 4, stride = 1, max_bytes = 65536, dim = 1024)` hashes the runs of `n` bytes of the same fields,
+/// and `binary` marks them instead of counting.
 ///
 #[proc_macro_derive(StructFeature, attributes(struct_feature))]
 pub fn derive_struct(input: TokenStream) -> TokenStream {
@@ -472,39 +2305,198 @@ pub fn derive_struct(input: TokenStream) -> TokenStream {
         _ => panic!("Need a Struct"),
     };
     let mut i = 0;
+    let mut k = 0;
     let name = syn::Ident::new("self",Span::call_site());
     let data = syn::Ident::new("data",Span::call_site());
     let mut self_field_setters: Vec<proc_macro2::TokenStream> = Vec::new();
     let mut name_field_setters: Vec<proc_macro2::TokenStream> = Vec::new();
-    for f in fields {
+    let mut fit_inits: Vec<proc_macro2::TokenStream> = Vec::new();
+    let mut fit_observers: Vec<proc_macro2::TokenStream> = Vec::new();
+    let mut fit_entries: Vec<proc_macro2::TokenStream> = Vec::new();
+    let mut target_inits: Vec<proc_macro2::TokenStream> = Vec::new();
+    let mut target_observers: Vec<proc_macro2::TokenStream> = Vec::new();
+    let mut target_assigns: Vec<proc_macro2::TokenStream> = Vec::new();
+    let mut fit_schema: Vec<proc_macro2::TokenStream> = Vec::new();
+    let mut name_pushes: Vec<proc_macro2::TokenStream> = Vec::new();
+    // A struct wide non_finite policy applies to every field without its own, but integer encodings.
+    let fields: Vec<syn::Field> = match non_finite_handler(attr_metas(&input.attrs, "struct_feature")) {
+        Some(policy) => fields
+            .iter()
+            .map(|f| {
+                let mut f = f.clone();
+                if non_finite_handler(field_metas(&f)).is_none() && !integer_encoded(&f) && flags_handler(&f).is_none() {
+                    f.attrs.push(syn::parse_quote!(#[struct_feature(non_finite = #policy)]));
+                }
+                f
+            })
+            .collect(),
+        None => fields.iter().cloned().collect(),
+    };
+    for f in &fields {
         if !detect_off(f) {
-            let (_iplus, k_name) = set_value_field(&name,i, f);
+            let (_iplus, k_name) = set_value_field(&name,i, k, f);
             self_field_setters.push(k_name);
-            let (iplus, k_data) = set_value_field(&data,i, f);
+            let (iplus, k_data) = set_value_field(&data,i, k, f);
             name_field_setters.push(k_data);
+            name_pushes.push(field_names(f, iplus - i));
             i = iplus;
+            if let Some(kind) = fitted_kind(f) {
+                let (init, observe, entry) = fit_field(k, f);
+                if kind == "target" {
+                    target_inits.push(init.clone());
+                    target_observers.push(observe.clone());
+                    target_assigns.push(quote! { fitted.fields[#k] = #entry; });
+                }
+                fit_inits.push(init);
+                fit_observers.push(observe);
+                fit_entries.push(entry);
+                let field_str = field_label(f);
+                let width = fitted_width(f);
+                fit_schema.push(quote! { (#field_str, #kind, #width) });
+                k += 1;
+            }
         }
     }
     let dim = i;
+    let featurizer_name = Ident::new(&format!("{}Featurizer{}", struct_name, dim), Span::call_site());
 
-    let featurizer_name = Ident::new(
-        &(struct_name.to_string() + &format!("Featurizer{}", dim)),
-        Span::call_site(),
-    );
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let (fitted_impl, fitted_get) = if k > 0 {
+        let struct_str = struct_name.to_string();
+        let fit_body = if target_inits.is_empty() {
+            quote! { Self::fit_labeled(iter.into_iter().map(|data| (data, f64::NAN))) }
+        } else {
+            let message = format!(
+                "{} has target encoded fields, fit it with fit_labeled or fit_out_of_fold",
+                struct_str
+            );
+            quote! { panic!(#message) }
+        };
+        let out_of_fold = if target_inits.is_empty() {
+            quote! {}
+        } else {
+            quote! {
+                /// One fitted state per fold, see `ruiso::target::fold_of`. The other fields are fitted
+                /// once on all rows, the target encoded ones on the rows of the other folds.
+                pub fn fit_out_of_fold(data: &[Self], labels: &[f64], folds: usize) -> Vec<ruiso::fitted::FittedStruct> {
+                    assert!(data.len() == labels.len(), "Need one label per struct");
+                    Self::ruiso_out_of_fold(&Self::fit_labeled(data.iter().zip(labels.iter().copied())), data, labels, folds)
+                }
+                /// Featurizes every row with the state fitted without its fold, so target encoded
+                /// fields never see their own label. The state fitted on all rows is installed afterwards.
+                pub fn featurize_out_of_fold(data: &[Self], labels: &[f64], folds: usize) -> Vec<Vec<f32>> {
+                    assert!(data.len() == labels.len(), "Need one label per struct");
+                    let full = Self::fit_labeled(data.iter().zip(labels.iter().copied()));
+                    let states = Self::ruiso_out_of_fold(&full, data, labels, folds);
+                    let rows = data
+                        .iter()
+                        .enumerate()
+                        .map(|(row, data)| {
+                            let mut features = vec![0.0; <Self as Featurizable>::dim()];
+                            data.ruiso_fill_with(&states[ruiso::target::fold_of(row, folds)], &mut features);
+                            features
+                        })
+                        .collect();
+                    Self::ruiso_fitted().install(full);
+                    rows
+                }
+                fn ruiso_fill_with(&self, fitted: &ruiso::fitted::FittedStruct, slice: &mut [f32]) {
+                    #(#self_field_setters);*;
+                }
+                fn ruiso_out_of_fold(
+                    full: &ruiso::fitted::FittedStruct,
+                    data: &[Self],
+                    labels: &[f64],
+                    folds: usize,
+                ) -> Vec<ruiso::fitted::FittedStruct> {
+                    assert!(folds > 1, "Out of fold fitting needs at least two folds");
+                    (0..folds)
+                        .map(|fold| {
+                            let mut fitted = full.clone();
+                            #(#target_inits)*
+                            for (row, (data, label)) in data.iter().zip(labels.iter().copied()).enumerate() {
+                                if ruiso::target::fold_of(row, folds) != fold {
+                                    #(#target_observers)*
+                                }
+                            }
+                            #(#target_assigns)*
+                            fitted
+                        })
+                        .collect()
+                }
+            }
+        };
+        let fitted_impl = quote! {
+            impl #impl_generics #struct_name #ty_generics #where_clause {
+                #[doc(hidden)]
+                pub fn ruiso_fitted() -> &'static ruiso::fitted::FittedSlot<ruiso::fitted::FittedStruct> {
+                    static SLOT: ruiso::fitted::FittedSlot<ruiso::fitted::FittedStruct> = ruiso::fitted::FittedSlot::new();
+                    &SLOT
+                }
+                /// Fits the fitted fields over the data. Install the result before featurizing.
+                /// Target encoded fields need labels, see `fit_labeled`.
+                /// Panics for structs with target encoded fields.
+                #[allow(unused_variables)]
+                pub fn fit<'ruiso, I: IntoIterator<Item = &'ruiso Self>>(iter: I) -> ruiso::fitted::FittedStruct {
+                    #fit_body
+                }
+                /// Fits the fitted fields over (struct, label) pairs.
+                #[allow(unused_variables)]
+                pub fn fit_labeled<'ruiso, I: IntoIterator<Item = (&'ruiso Self, f64)>>(iter: I) -> ruiso::fitted::FittedStruct {
+                    #(#fit_inits)*
+                    for (data, label) in iter {
+                        #(#fit_observers)*
+                    }
+                    ruiso::fitted::FittedStruct { fields: vec![#(#fit_entries),*] }
+                }
+                #out_of_fold
+                /// Installs fitted state for every following featurization of this struct.
+                pub fn install_fitted(fitted: ruiso::fitted::FittedStruct) -> std::io::Result<()> {
+                    fitted.check_schema(&[#(#fit_schema),*])?;
+                    Self::ruiso_fitted().install(fitted);
+                    Ok(())
+                }
+                /// Loads fitted state saved with `Persist::save` and installs it.
+                pub fn load_fitted<P: AsRef<std::path::Path>>(path: P) -> std::io::Result<()> {
+                    Self::install_fitted(<ruiso::fitted::FittedStruct as ruiso::fitted::Persist>::load(path)?)
+                }
+            }
+        };
+        let fitted_get = quote! {
+            let fitted = <#struct_name #ty_generics>::ruiso_fitted().expect(#struct_str);
+        };
+        (fitted_impl, fitted_get)
+    } else {
+        (quote! {}, quote! {})
+    };
 
     let trait_impl = quote! {
-        impl Featurizable for #struct_name {
+        #fitted_impl
+
+        impl #impl_generics #struct_name #ty_generics #where_clause {
+            /// Names of the columns, in order: `field` for single columns, `field:name` for named ones
+            /// and `field[j]` for the others.
+            pub fn feature_names() -> Vec<String> {
+                let mut names: Vec<String> = Vec::new();
+                #(#name_pushes)*
+                names
+            }
+        }
+
+        impl #impl_generics Featurizable for #struct_name #ty_generics #where_clause {
             fn dim() -> usize {#dim}
             fn fill_slice(&self, slice:&mut [f32]) {
+                #fitted_get
                 #(#self_field_setters);*;
             }
             fn default(_slice: &mut [f32]) {}
         }
 
         pub struct #featurizer_name{}
-        impl Featurizer<#struct_name> for #featurizer_name {
+        impl #impl_generics Featurizer<#struct_name #ty_generics> for #featurizer_name #where_clause {
             fn dim() -> usize {#dim}
-            fn fill_slice(data:&#struct_name, slice:&mut [f32]) {
+            fn fill_slice(data:&#struct_name #ty_generics, slice:&mut [f32]) {
+                #fitted_get
                 #(#name_field_setters);*;
             }
             fn default(_slice: &mut [f32]) {}